@@ -1,70 +1,247 @@
-use crate::utils::TokenTreeExt;
-use anyhow::{bail, Result};
-use proc_macro2::{Ident, Literal, Span, TokenStream, TokenTree};
-use quote::quote;
-use std::collections::HashMap;
+use crate::utils::{bail, unescape, AttributeMap, Errors, TokenTreeExt};
+use proc_macro2::{Delimiter, Group, Ident, Literal, Span, TokenStream, TokenTree};
+use quote::{quote, ToTokens};
 use syn::{
-    self, spanned::Spanned, Attribute, Data, DataStruct, DeriveInput, Fields, Lifetime,
-    LifetimeParam,
+    self, spanned::Spanned, Attribute, Data, DataEnum, DataStruct, DeriveInput, Fields, Generics,
+    Index, Lifetime, LifetimeParam, Member, Result, Type,
 };
 
 #[derive(Debug, Clone)]
-enum SchmargsAttribute {
-    Arg(ArgAttribute),
-    Doc(DocAttribute),
+pub(crate) enum SchmargsAttribute {
+    Arg(Box<ArgAttribute>),
+    // A single line of a doc comment
+    Doc(String),
     TopLevel(TopLevelAttribute),
+    Value(ValueAttribute),
 }
 
 #[derive(Debug, Clone)]
-struct TopLevelAttribute {
+pub(crate) struct TopLevelAttribute {
     // What kind of string this should iterate over
     // e.g. String or &str (default: &str)
     iterates_over: Option<Ident>,
     // Name of the program
     name: Option<Literal>,
+    // Prefix of the environment variables of arguments with an implicit `env`
+    env_prefix: Option<Literal>,
+    // Groups of related arguments
+    groups: Vec<GroupAttribute>,
+    // Long flag that shows the help text of hidden arguments, e.g. "--help-all"
+    show_hidden_with: Option<Literal>,
 }
 
+// A group of arguments, e.g. `group(name = "transport", args = [tcp, udp], required)`
 #[derive(Debug, Clone)]
-struct ArgAttribute {
+pub(crate) struct GroupAttribute {
+    name: String,
+    args: Vec<Ident>,
+    // At least one of the arguments must be passed
+    required: bool,
+    // More than one of the arguments can be passed
+    multiple: bool,
+}
+
+impl GroupAttribute {
+    fn parse(group: &Group) -> Result<Self> {
+        let mut map = parse_attribute_map(group.stream())?;
+        let Some(name) = map.remove_value("name")? else {
+            bail!(group, "Groups need a `name`");
+        };
+        let multiple = map.remove_bool("multiple")?.unwrap_or(true);
+        let group = Self {
+            name: unescape(&name.into_str_literal()?),
+            args: map
+                .remove_value("args")?
+                .map(TokenTreeExt::into_ident_list)
+                .transpose()?
+                .unwrap_or_default(),
+            required: map.remove_bool("required")?.unwrap_or_default(),
+            multiple,
+        };
+        map.finish()?;
+        Ok(group)
+    }
+
+    // Heading in the help text, e.g. "Transport"
+    fn heading(&self) -> String {
+        let mut chars = self.name.chars();
+        chars
+            .next()
+            .map(|first| first.to_uppercase().chain(chars).collect())
+            .unwrap_or_default()
+    }
+
+    // Are all arguments of the group hidden, so its heading is too
+    fn is_hidden(&self, args: &[Arg]) -> bool {
+        self.args
+            .iter()
+            .all(|ident| find_arg(args, ident).is_hidden())
+    }
+
+    // Shown in the usage text, e.g. " <--tcp|--udp>". Hidden arguments are left out
+    fn usage(&self, args: &[Arg]) -> String {
+        let members: Vec<_> = self
+            .args
+            .iter()
+            .map(|ident| find_arg(args, ident))
+            .filter(|arg| !arg.is_hidden())
+            .map(Arg::flag_name)
+            .collect();
+        if members.is_empty() {
+            return String::new();
+        }
+        let members = members.join("|");
+        if self.required {
+            format!(" <{members}>")
+        } else {
+            format!(" [{members}]")
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct ArgAttribute {
     short: Option<Option<Literal>>,
     long: Option<Option<Literal>>,
     value_name: Option<Literal>,
-    default_value: Option<TokenStream>,
+    // Expression of the default value, if it's not `Default::default()`
+    default_value: Option<Option<TokenStream>>,
+    // Parse this field's arguments as part of the parent
+    flatten: bool,
+    // Prefix of the long flags of a flattened field
+    prefix: Option<Literal>,
+    // What to do when the flag is encountered, e.g. `count`
+    action: Option<Ident>,
+    // Also accept `--no-<long>`, which sets the flag to false
+    negatable: bool,
+    // Range that the value must be in, e.g. `1..=65535`
+    range: Option<TokenStream>,
+    // Values that the value must be one of
+    possible_values: Option<Vec<Literal>>,
+    // Minimum number of values of a collection
+    min_len: Option<Literal>,
+    // Maximum number of values of a collection
+    max_len: Option<Literal>,
+    // Function used to parse values instead of `SchmargsField::parse_str`
+    value_parser: Option<TokenStream>,
+    // Arguments that must be passed if this one is
+    requires: Vec<Ident>,
+    // Arguments that can't be passed if this one is
+    conflicts_with: Vec<Ident>,
+    // This argument must be passed unless one of these is
+    required_unless_present: Vec<Ident>,
+    // Environment variable to fall back on
+    env: Option<Option<Literal>>,
+    // Additional long flags that aren't shown in help text
+    aliases: Vec<Literal>,
+    // Additional long flags that are shown in help text
+    visible_aliases: Vec<Literal>,
+    // Additional short flags that aren't shown in help text
+    short_aliases: Vec<Literal>,
+    // Additional short flags that are shown in help text
+    visible_short_aliases: Vec<Literal>,
+    // Leave out of the help and usage text
+    hide: bool,
+    // What kind of value the argument takes, e.g. `dir`
+    value_hint: Option<Ident>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-struct DocAttribute {
-    value: String,
+// Doc comment of a command or argument
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct DocAttribute {
+    // First paragraph, used in the help text
+    short: String,
+    // All paragraphs, used in the long help text
+    long: String,
+}
+
+impl DocAttribute {
+    // Build from the lines of a doc comment. Lines are joined into paragraphs, which are separated
+    // by blank lines
+    fn from_lines(lines: &[String]) -> Self {
+        let mut paragraphs: Vec<String> = Vec::new();
+        let mut in_paragraph = false;
+        for line in lines.iter().flat_map(|line| line.split('\n')) {
+            let line = line.trim();
+            if line.is_empty() {
+                in_paragraph = false;
+                continue;
+            }
+            match paragraphs.last_mut() {
+                Some(paragraph) if in_paragraph => {
+                    paragraph.push(' ');
+                    paragraph.push_str(line);
+                }
+                _ => paragraphs.push(line.into()),
+            }
+            in_paragraph = true;
+        }
+        Self {
+            short: paragraphs.first().cloned().unwrap_or_default(),
+            long: paragraphs.join("\n\n"),
+        }
+    }
 }
 
+// Attribute on a variant of a `SchmargsValue` enum
 #[derive(Debug, Clone)]
+pub(crate) struct ValueAttribute {
+    // Value used on the command line
+    pub(crate) name: Option<Literal>,
+    // Additional values that are accepted, but not shown in help text
+    pub(crate) aliases: Vec<Literal>,
+}
+
+#[derive(Debug, Clone, Default)]
 struct AttributeAggregate {
     doc: DocAttribute,
     arg: Option<ArgAttribute>,
     top_level: Option<TopLevelAttribute>,
 }
 
+impl AttributeAggregate {
+    fn groups(&self) -> &[GroupAttribute] {
+        self.top_level
+            .as_ref()
+            .map(|v| v.groups.as_slice())
+            .unwrap_or_default()
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 enum ArgKind {
     Flag,
+    // Flag that counts its occurrences, e.g. `-vvv`
+    Count,
     Option,
     Positional,
+    Flatten,
 }
 
 #[derive(Debug, Clone)]
 struct Arg {
     attr: AttributeAggregate,
+    // Name of the argument. Fields of tuple structs are named after their index, e.g. `arg0`
     ident: Ident,
+    // Field the argument is stored in
+    member: Member,
+    ty: Type,
     is_bool: bool,
     is_option: bool,
     is_collection: bool,
+    // Name of the environment variable to fall back on
+    env: Option<String>,
 }
 
 impl Arg {
     fn kind(&self) -> ArgKind {
         if let Some(arg) = &self.attr.arg {
-            if arg.short.is_none() && arg.long.is_none() {
+            if arg.flatten {
+                ArgKind::Flatten
+            } else if arg.short.is_none() && arg.long.is_none() {
                 ArgKind::Positional
+            } else if arg.action.as_ref().is_some_and(|v| v == "count") {
+                ArgKind::Count
             } else if self.is_bool {
                 ArgKind::Flag
             } else {
@@ -100,16 +277,64 @@ impl Arg {
         }) = &self.attr.arg
         {
             let long: String = long
-                .clone()
-                .map(|v| v.to_string())
+                .as_ref()
+                .map(unescape)
                 .unwrap_or_else(|| self.ident.to_string().replace('_', "-"));
-            let long = String::from("--")
-                + &snailquote::unescape(&long).expect("Failed to unescape string");
-            return Some(long);
+            return Some(String::from("--") + &long);
         }
         None
     }
 
+    // Long aliases, without the leading "--". Only includes visible aliases if `visible_only`
+    fn long_aliases(&self, visible_only: bool) -> Vec<String> {
+        let Some(arg) = &self.attr.arg else {
+            return Vec::new();
+        };
+        let hidden = if visible_only { &[][..] } else { &arg.aliases };
+        arg.visible_aliases
+            .iter()
+            .chain(hidden)
+            .map(unescape)
+            .collect()
+    }
+
+    // Short aliases. Only includes visible aliases if `visible_only`
+    fn short_aliases(&self, visible_only: bool) -> Vec<Literal> {
+        let Some(arg) = &self.attr.arg else {
+            return Vec::new();
+        };
+        let hidden = if visible_only {
+            &[][..]
+        } else {
+            &arg.short_aliases
+        };
+        arg.visible_short_aliases
+            .iter()
+            .chain(hidden)
+            .cloned()
+            .collect()
+    }
+
+    // Return as "--no-long" if the flag is negatable
+    fn negated_long(&self) -> Option<String> {
+        match &self.attr.arg {
+            Some(ArgAttribute {
+                negatable: true, ..
+            }) => self.long().map(|long| long.replacen("--", "--no-", 1)),
+            _ => None,
+        }
+    }
+
+    // Return as "--long", or "--[no-]long" if the flag is negatable
+    fn display_long(&self) -> Option<String> {
+        let long = self.long()?;
+        if self.negated_long().is_some() {
+            Some(long.replacen("--", "--[no-]", 1))
+        } else {
+            Some(long)
+        }
+    }
+
     // Return value name
     fn value_name(&self) -> String {
         if let Some(ArgAttribute {
@@ -117,148 +342,492 @@ impl Arg {
             ..
         }) = &self.attr.arg
         {
-            snailquote::unescape(&value_name.to_string()).expect("Could not unescape string")
+            unescape(value_name)
         } else {
             self.ident.to_string().to_uppercase()
         }
     }
 
+    // Prefix of the long flags of a flattened field
+    fn prefix(&self) -> String {
+        if let Some(ArgAttribute {
+            prefix: Some(prefix),
+            ..
+        }) = &self.attr.arg
+        {
+            unescape(prefix)
+        } else {
+            String::new()
+        }
+    }
+
+    // Constraints on the value of the argument, as shown in the help text, along with an
+    // expression that checks `__schmargs_value`
+    fn constraints(&self) -> Vec<(String, TokenStream)> {
+        let mut constraints = Vec::new();
+        let Some(arg) = &self.attr.arg else {
+            return constraints;
+        };
+
+        // Check each value of an `Option` or collection
+        let each = |check: TokenStream| {
+            if self.is_option || self.is_collection {
+                quote! { __schmargs_value.iter().all(|__schmargs_value| #check) }
+            } else {
+                quote! { { let __schmargs_value = &__schmargs_value; #check } }
+            }
+        };
+
+        if let Some(range) = &arg.range {
+            constraints.push((
+                format!("range: {}", range.to_string().replace(' ', "")),
+                each(quote! { (#range).contains(__schmargs_value) }),
+            ));
+        }
+        if let Some(values) = &arg.possible_values {
+            let list = values.iter().map(unescape).collect::<Vec<_>>().join(", ");
+            constraints.push((
+                format!("possible values: {list}"),
+                each(quote! {
+                    [#(#values),*].contains(&::core::convert::AsRef::<str>::as_ref(__schmargs_value))
+                }),
+            ));
+        }
+        if let Some(min_len) = &arg.min_len {
+            constraints.push((
+                format!("min values: {min_len}"),
+                quote! { __schmargs_value.len() >= #min_len },
+            ));
+        }
+        if let Some(max_len) = &arg.max_len {
+            constraints.push((
+                format!("max values: {max_len}"),
+                quote! { __schmargs_value.len() <= #max_len },
+            ));
+        }
+        constraints
+    }
+
+    // Arguments named by `requires`, `conflicts_with`, and `required_unless_present`
+    fn relations(&self) -> impl Iterator<Item = &Ident> {
+        self.attr.arg.iter().flat_map(|arg| {
+            arg.requires
+                .iter()
+                .chain(&arg.conflicts_with)
+                .chain(&arg.required_unless_present)
+        })
+    }
+
+    // Return as "--long" or "-s"
+    fn flag_name(&self) -> String {
+        self.long().unwrap_or_else(|| {
+            let short = self.short().expect("Bug: expected flag");
+            format!("-{}", unescape(&short))
+        })
+    }
+
+    // Name of the argument in error messages, e.g. "--output" or "FILE"
+    fn display_name(&self) -> String {
+        if self.kind() == ArgKind::Positional {
+            self.value_name()
+        } else {
+            self.flag_name()
+        }
+    }
+
+    // Is the argument left out of the help and usage text
+    fn is_hidden(&self) -> bool {
+        self.attr.arg.as_ref().is_some_and(|arg| arg.hide)
+    }
+
+    // Return the group the argument is in, if any
+    fn group<'g>(&self, groups: &'g [GroupAttribute]) -> Option<&'g GroupAttribute> {
+        groups.iter().find(|group| group.args.contains(&self.ident))
+    }
+
+    // Expression that evaluates to true if the argument was passed
+    fn is_present(&self) -> TokenStream {
+        let ident = self.unique_ident();
+        match self.kind() {
+            ArgKind::Flag => quote! { #ident },
+            ArgKind::Count => quote! { (#ident != 0) },
+            ArgKind::Option | ArgKind::Positional => quote! { #ident.is_some() },
+            ArgKind::Flatten => unreachable!("Bug: flattened fields can't be checked for presence"),
+        }
+    }
+
+    // A required option isn't an `Option` and has no default, e.g. `--name <NAME>`
+    fn is_required_option(&self) -> bool {
+        self.kind() == ArgKind::Option
+            && !self.is_option
+            && self.default_value().is_none()
+            && self
+                .attr
+                .arg
+                .as_ref()
+                .is_none_or(|attr| attr.required_unless_present.is_empty())
+    }
+
+    // Is the argument summarized as "[OPTIONS]" in the usage text. Required options are listed on
+    // their own
+    fn is_usage_option(&self) -> bool {
+        match self.kind() {
+            ArgKind::Flag | ArgKind::Count => !self.is_hidden(),
+            ArgKind::Option => !self.is_required_option() && !self.is_hidden(),
+            ArgKind::Positional | ArgKind::Flatten => false,
+        }
+    }
+
+    // Can the argument be left out. Flags always can
+    fn is_optional(&self) -> bool {
+        match self.kind() {
+            ArgKind::Flag | ArgKind::Count | ArgKind::Flatten => true,
+            ArgKind::Option => !self.is_required_option(),
+            ArgKind::Positional => {
+                self.is_option
+                    || self.default_value().is_some()
+                    || self.env.is_some()
+                    || self
+                        .attr
+                        .arg
+                        .as_ref()
+                        .is_some_and(|attr| !attr.required_unless_present.is_empty())
+            }
+        }
+    }
+
+    // Usage of a required option, split where the prefix goes, e.g. `("--", "name <NAME>")`
+    fn required_usage(&self) -> (String, String) {
+        let value_name = self.value_name();
+        let name = self.flag_name();
+        let dashes = if self.long().is_some() { "--" } else { "-" };
+        let rest = name.strip_prefix(dashes).expect("Bug: expected dashes");
+        let ellipsis = if self.is_collection { "..." } else { "" };
+        (dashes.into(), format!("{rest} <{value_name}>{ellipsis}"))
+    }
+
     fn default_value(&self) -> Option<TokenStream> {
         if let Some(ArgAttribute {
             default_value: Some(default_value),
             ..
         }) = &self.attr.arg
         {
-            Some(default_value.clone())
+            Some(
+                default_value
+                    .clone()
+                    .unwrap_or(quote! {::core::default::Default::default()}),
+            )
         } else {
             None
         }
     }
+
+    // Default value as shown in generated documentation, e.g. "8080". Only known if it's given
+    // as an expression
+    fn default_value_text(&self) -> Option<String> {
+        let default_value = self.attr.arg.as_ref()?.default_value.as_ref()?.as_ref()?;
+        Some(match syn::parse2::<syn::Lit>(default_value.clone()) {
+            Ok(syn::Lit::Str(string)) => string.value(),
+            Ok(syn::Lit::Char(char)) => char.value().to_string(),
+            _ => default_value.to_string().replace(' ', ""),
+        })
+    }
 }
 
-fn parse_attribute(attr: &Attribute) -> Result<SchmargsAttribute> {
-    match attr.meta {
-        syn::Meta::List(ref list) => {
-            let tokens = list.parse_args::<TokenStream>().unwrap();
-
-            let mut map = HashMap::new();
-
-            let mut key = None;
-            for token in tokens {
-                match token {
-                    ident @ TokenTree::Ident(_) => {
-                        if let Some(key) = key.take() {
-                            map.insert(key, Some(ident));
-                        } else {
-                            key = Some(ident.to_string());
-                        }
-                    }
-                    TokenTree::Punct(punct) => match punct.as_char() {
-                        ',' => {
-                            if let Some(key) = key.take() {
-                                map.insert(key, None);
-                            }
-                        }
-                        '=' => {}
-                        _ => {
-                            bail!("Unexpected punctuation in attribute");
-                        }
-                    },
-                    literal @ TokenTree::Literal(_) => {
-                        if let Some(key) = key.take() {
-                            map.insert(key, Some(literal));
-                        } else {
-                            bail!("Unexpected literal in attribute")
-                        }
+#[derive(Debug, Clone)]
+enum CommandKind {
+    // Subcommand with its own named arguments, e.g. `Add { force: bool }`
+    Struct(Vec<Arg>),
+    // Subcommand that wraps another Schmargs type, e.g. `Remote(RemoteArgs)`
+    Newtype(Box<Type>),
+}
+
+#[derive(Debug, Clone)]
+struct Command {
+    attr: AttributeAggregate,
+    ident: Ident,
+    kind: CommandKind,
+}
+
+impl Command {
+    // Name used on the command line, e.g. `AddFile` becomes "add-file"
+    fn name(&self) -> String {
+        if let Some(TopLevelAttribute {
+            name: Some(name), ..
+        }) = &self.attr.top_level
+        {
+            unescape(name)
+        } else {
+            crate::utils::to_kebab_case(&self.ident.to_string())
+        }
+    }
+}
+
+// Parse the key-value pairs of an attribute, e.g. `short, long = "foo"`
+fn parse_attribute_map(tokens: TokenStream) -> Result<AttributeMap> {
+    let mut map = AttributeMap::default();
+
+    let mut key: Option<Ident> = None;
+    // Tokens after the "=" of a key-value pair, e.g. `1..=10` in `range = 1..=10`
+    let mut value: Option<Vec<TokenTree>> = None;
+    for token in tokens {
+        if let Some(value) = &mut value {
+            if !matches!(&token, TokenTree::Punct(punct) if punct.as_char() == ',') {
+                value.push(token);
+                continue;
+            }
+        }
+        match token {
+            TokenTree::Ident(ident) => {
+                if let Some(key) = key.take() {
+                    map.insert(key, Some(TokenTree::Ident(ident)));
+                } else {
+                    key = Some(ident);
+                }
+            }
+            TokenTree::Punct(punct) => match punct.as_char() {
+                ',' => {
+                    if let Some(key) = key.take() {
+                        let value = value.take().map(|v| attribute_value(&key, v)).transpose()?;
+                        map.insert(key, value);
                     }
-                    TokenTree::Group(_) => bail!("Unexpected token tree type"),
+                }
+                '=' if key.is_some() => {
+                    value = Some(Vec::new());
+                }
+                _ => {
+                    bail!(punct, "Unexpected punctuation in attribute");
+                }
+            },
+            TokenTree::Literal(literal) => {
+                if let Some(key) = key.take() {
+                    map.insert(key, Some(TokenTree::Literal(literal)));
+                } else {
+                    bail!(literal, "Unexpected literal in attribute")
                 }
             }
-            if let Some(key) = key.take() {
-                map.insert(key, None);
+            // Nested attribute, e.g. `group(name = "a", args = [b, c])`
+            TokenTree::Group(group) if group.delimiter() == Delimiter::Parenthesis => {
+                if let Some(key) = key.take() {
+                    map.insert(key, Some(TokenTree::Group(group)));
+                } else {
+                    bail!(group, "Unexpected nested attribute")
+                }
             }
+            TokenTree::Group(group) => bail!(group, "Unexpected token tree type"),
+        }
+    }
+    if let Some(key) = key.take() {
+        let value = value.take().map(|v| attribute_value(&key, v)).transpose()?;
+        map.insert(key, value);
+    }
+
+    Ok(map)
+}
+
+pub(crate) fn parse_attribute(attr: &Attribute) -> Result<SchmargsAttribute> {
+    match attr.meta {
+        syn::Meta::List(ref list) => {
+            let tokens = list.parse_args::<TokenStream>()?;
+
+            let mut map = parse_attribute_map(tokens)?;
+
+            // Values of a key that can be repeated, e.g. `alias = "a", alias = "b"`
+            let literals =
+                |map: &mut AttributeMap, key: &str, unwrap: fn(TokenTree) -> Result<Literal>| {
+                    map.remove_all_values(key)?
+                        .into_iter()
+                        .map(unwrap)
+                        .collect::<Result<Vec<_>>>()
+                };
+            // Arguments named by a key that can be repeated, e.g. `requires = [a, b], requires = c`
+            let idents = |map: &mut AttributeMap, key: &str| -> Result<Vec<Ident>> {
+                let mut idents = Vec::new();
+                for value in map.remove_all_values(key)? {
+                    idents.extend(value.into_ident_list()?);
+                }
+                Ok(idents)
+            };
 
             let return_value = if attr.path().is_ident("arg") {
-                SchmargsAttribute::Arg(ArgAttribute {
+                SchmargsAttribute::Arg(Box::new(ArgAttribute {
                     short: map
                         .remove("short")
-                        .map(|v| v.map(|v| v.unwrap_as_literal())),
-                    long: map.remove("long").map(|v| v.map(|v| v.unwrap_as_literal())),
+                        .map(|v| v.map(TokenTreeExt::into_char_literal).transpose())
+                        .transpose()?,
+                    long: map
+                        .remove("long")
+                        .map(|v| v.map(TokenTreeExt::into_str_literal).transpose())
+                        .transpose()?,
                     value_name: map
-                        .remove("value_name")
-                        .flatten()
-                        .map(|v| v.unwrap_as_literal()),
-                    default_value: map.remove("default_value").map(|v| {
-                        v.map(|v| quote! {#v})
-                            .unwrap_or(quote! {::core::default::Default::default()})
-                    }),
-                })
+                        .remove_value("value_name")?
+                        .map(TokenTreeExt::into_str_literal)
+                        .transpose()?,
+                    default_value: map.remove("default_value").map(|v| v.map(|v| quote! {#v})),
+                    flatten: map.remove_bool("flatten")?.unwrap_or_default(),
+                    prefix: map
+                        .remove_value("prefix")?
+                        .map(TokenTreeExt::into_str_literal)
+                        .transpose()?,
+                    action: map
+                        .remove_value("action")?
+                        .map(TokenTreeExt::into_ident)
+                        .transpose()?,
+                    negatable: map.remove_bool("negatable")?.unwrap_or_default(),
+                    range: map.remove_value("range")?.map(|v| quote! {#v}),
+                    possible_values: map
+                        .remove_value("possible_values")?
+                        .map(TokenTreeExt::into_str_literal_list)
+                        .transpose()?,
+                    min_len: map
+                        .remove_value("min_len")?
+                        .map(TokenTreeExt::into_int_literal)
+                        .transpose()?,
+                    max_len: map
+                        .remove_value("max_len")?
+                        .map(TokenTreeExt::into_int_literal)
+                        .transpose()?,
+                    value_parser: map.remove_value("value_parser")?.map(|v| quote! {#v}),
+                    requires: idents(&mut map, "requires")?,
+                    conflicts_with: idents(&mut map, "conflicts_with")?,
+                    required_unless_present: idents(&mut map, "required_unless_present")?,
+                    env: map
+                        .remove("env")
+                        .map(|v| v.map(TokenTreeExt::into_str_literal).transpose())
+                        .transpose()?,
+                    aliases: literals(&mut map, "alias", TokenTreeExt::into_str_literal)?,
+                    visible_aliases: literals(
+                        &mut map,
+                        "visible_alias",
+                        TokenTreeExt::into_str_literal,
+                    )?,
+                    short_aliases: literals(
+                        &mut map,
+                        "short_alias",
+                        TokenTreeExt::into_char_literal,
+                    )?,
+                    visible_short_aliases: literals(
+                        &mut map,
+                        "visible_short_alias",
+                        TokenTreeExt::into_char_literal,
+                    )?,
+                    hide: map.remove_bool("hide")?.unwrap_or_default(),
+                    value_hint: map
+                        .remove_value("value_hint")?
+                        .map(TokenTreeExt::into_ident)
+                        .transpose()?,
+                }))
             } else if attr.path().is_ident("schmargs") {
                 SchmargsAttribute::TopLevel(TopLevelAttribute {
                     iterates_over: map
-                        .remove("iterates_over")
-                        .map(|v| v.expect("`iterates_over` expects a type"))
-                        .map(|v| v.unwrap_as_ident()),
+                        .remove_value("iterates_over")?
+                        .map(TokenTreeExt::into_ident)
+                        .transpose()?,
+                    name: map
+                        .remove_value("name")?
+                        .map(TokenTreeExt::into_str_literal)
+                        .transpose()?,
+                    env_prefix: map
+                        .remove_value("env_prefix")?
+                        .map(TokenTreeExt::into_str_literal)
+                        .transpose()?,
+                    groups: map
+                        .remove_all_values("group")?
+                        .into_iter()
+                        .map(|v| match v {
+                            TokenTree::Group(group) => GroupAttribute::parse(&group),
+                            v => bail!(v, "`group` expects a list of arguments"),
+                        })
+                        .collect::<Result<_>>()?,
+                    show_hidden_with: map
+                        .remove_value("show_hidden_with")?
+                        .map(TokenTreeExt::into_str_literal)
+                        .transpose()?,
+                })
+            } else if attr.path().is_ident("value") {
+                SchmargsAttribute::Value(ValueAttribute {
                     name: map
-                        .remove("name")
-                        .map(|v| v.expect("`name` expects a type"))
-                        .map(|v| v.unwrap_as_literal()),
+                        .remove_value("name")?
+                        .map(TokenTreeExt::into_str_literal)
+                        .transpose()?,
+                    aliases: literals(&mut map, "alias", TokenTreeExt::into_str_literal)?,
                 })
             } else {
-                bail!("Unsupported attribute type");
+                bail!(attr, "Unsupported attribute type");
             };
 
-            if !map.is_empty() {
-                bail!("Unknown argument to attribute");
-            }
+            map.finish()?;
 
             Ok(return_value)
         }
-        syn::Meta::NameValue(ref pair) => {
-            assert!(attr.path().is_ident("doc"));
+        syn::Meta::NameValue(ref pair) if attr.path().is_ident("doc") => {
             let syn::Expr::Lit(ref value) = pair.value else {
-                bail!("Expected literal attribute value ( i.e. doc comment)");
+                bail!(
+                    &pair.value,
+                    "Expected literal attribute value (i.e. doc comment)"
+                );
             };
             let syn::Lit::Str(ref value) = value.lit else {
-                bail!("Expected str literal attribute value ( i.e. doc comment)");
+                bail!(
+                    &value.lit,
+                    "Expected str literal attribute value (i.e. doc comment)"
+                );
             };
-            Ok(SchmargsAttribute::Doc(DocAttribute {
-                value: value.value().trim().into(),
-            }))
+            Ok(SchmargsAttribute::Doc(value.value()))
         }
-        _ => bail!("Expected name-value pair attribute (i.e. doc comment)"),
+        _ => bail!(attr, "Expected a list attribute, e.g. `#[arg(short)]`"),
+    }
+}
+
+// Value of a key-value pair in an attribute. Values that span multiple tokens, like `1..=10`, are
+// wrapped in an undelimited group
+fn attribute_value(key: &Ident, tokens: Vec<TokenTree>) -> Result<TokenTree> {
+    match <[TokenTree; 1]>::try_from(tokens) {
+        Ok([token]) => Ok(token),
+        Err(tokens) if tokens.is_empty() => bail!(key, "`{key}` expects a value"),
+        Err(tokens) => Ok(TokenTree::Group(Group::new(
+            Delimiter::None,
+            tokens.into_iter().collect(),
+        ))),
     }
 }
 
 fn parse_attributes(attrs: &[Attribute]) -> Result<AttributeAggregate> {
-    let mut doc = None;
+    let mut doc = Vec::new();
     let mut arg = None;
     let mut top_level = None;
+    // Attributes of other macros are ignored
+    let attrs = attrs.iter().filter(|attr| {
+        ["doc", "arg", "schmargs", "value"]
+            .iter()
+            .any(|name| attr.path().is_ident(name))
+    });
     for attr in attrs {
         match parse_attribute(attr)? {
-            SchmargsAttribute::Doc(attr) => {
-                if doc.is_some() {
-                    bail!("Was not expecting two doc attributes!");
-                }
-                doc = Some(attr);
-            }
-            SchmargsAttribute::Arg(attr) => {
+            SchmargsAttribute::Doc(line) => doc.push(line),
+            SchmargsAttribute::Arg(parsed) => {
                 if arg.is_some() {
-                    bail!("Was not expecting two arg attributes!");
+                    bail!(attr, "Was not expecting two `arg` attributes");
                 }
-                arg = Some(attr);
+                arg = Some(*parsed);
             }
-            SchmargsAttribute::TopLevel(attr) => {
+            SchmargsAttribute::TopLevel(parsed) => {
                 if top_level.is_some() {
-                    bail!("Was not expecting two arg attributes!");
+                    bail!(attr, "Was not expecting two `schmargs` attributes");
                 }
-                top_level = Some(attr);
+                top_level = Some(parsed);
+            }
+            SchmargsAttribute::Value(_) => {
+                bail!(
+                    attr,
+                    "`value` attributes are only supported when deriving `SchmargsValue`"
+                );
             }
         }
     }
 
     Ok(AttributeAggregate {
-        doc: doc.expect("Missing doc attribute"),
+        doc: DocAttribute::from_lines(&doc),
         arg,
         top_level,
     })
@@ -266,7 +835,10 @@ fn parse_attributes(attrs: &[Attribute]) -> Result<AttributeAggregate> {
 
 pub fn schmargs_derive_impl(input: DeriveInput) -> Result<proc_macro::TokenStream> {
     let struct_name = input.ident;
-    let attributes = parse_attributes(&input.attrs)?;
+    let mut errors = Errors::default();
+    let attributes = errors
+        .check(parse_attributes(&input.attrs))
+        .unwrap_or_default();
     let command_name = attributes
         .top_level
         .clone()
@@ -275,15 +847,130 @@ pub fn schmargs_derive_impl(input: DeriveInput) -> Result<proc_macro::TokenStrea
         .unwrap_or_else(|| {
             quote! {env!("CARGO_PKG_NAME")}
         });
-    let description = attributes.doc.value;
+    let env_prefix = attributes
+        .top_level
+        .as_ref()
+        .and_then(|v| v.env_prefix.as_ref())
+        .map(unescape)
+        .unwrap_or_default();
+    let iterates_over = attributes
+        .top_level
+        .as_ref()
+        .and_then(|v| v.iterates_over.as_ref());
+    let show_hidden_with = attributes
+        .top_level
+        .as_ref()
+        .and_then(|v| v.show_hidden_with.as_ref());
+    if let Some(flag) = show_hidden_with {
+        let value = unescape(flag);
+        if value.len() <= 2 || !value.starts_with("--") {
+            errors.push(syn::Error::new_spanned(
+                flag,
+                "`show_hidden_with` expects a long flag, e.g. \"--help-all\"",
+            ));
+        }
+    }
+
+    let (args, commands) = match &input.data {
+        Data::Struct(DataStruct { fields, .. }) => {
+            let args = parse_fields(fields, &env_prefix, &mut errors);
+            if let Some(flag) = show_hidden_with {
+                let value = unescape(flag);
+                if let Some(arg) = args.iter().find(|a| {
+                    a.long().as_ref() == Some(&value) || a.negated_long().as_ref() == Some(&value)
+                }) {
+                    errors.push(syn::Error::new_spanned(
+                        flag,
+                        format!("Flag `{value}` is already used by `{}`", arg.ident),
+                    ));
+                }
+            }
+            errors.check(validate_groups(&args, attributes.groups()));
+            (args, Vec::new())
+        }
+        Data::Enum(data) => {
+            if let Some(flag) = show_hidden_with {
+                errors.push(syn::Error::new_spanned(
+                    flag,
+                    "`show_hidden_with` can only be used on structs",
+                ));
+            }
+            (Vec::new(), parse_variants(data, &env_prefix, &mut errors))
+        }
+        Data::Union(_) => bail!(struct_name, "expected a struct or an enum"),
+    };
+    errors.finish()?;
+
+    let body = match &input.data {
+        Data::Enum(_) => Body::Enum(&commands),
+        _ => Body::Struct(&args, attributes.groups()),
+    };
+    let mut gen = impl_schmargs(&SchmargsInput {
+        struct_name: &struct_name,
+        generics: &input.generics,
+        command_name: command_name.clone(),
+        iterates_over,
+        show_hidden_with,
+        doc: &attributes.doc,
+        body,
+        marker: false,
+    });
+    for command in &commands {
+        if let CommandKind::Struct(args) = &command.kind {
+            gen.extend(impl_variant_struct(
+                &struct_name,
+                &input.generics,
+                &command_name,
+                iterates_over,
+                command,
+                args,
+            ));
+        }
+    }
+
+    Ok(gen.into())
+}
+
+// Arguments of a struct, or subcommands of an enum
+#[derive(Clone, Copy)]
+enum Body<'a> {
+    Struct(&'a [Arg], &'a [GroupAttribute]),
+    Enum(&'a [Command]),
+}
+
+// A parsed type to implement `Schmargs` for
+struct SchmargsInput<'a> {
+    struct_name: &'a Ident,
+    generics: &'a Generics,
+    command_name: TokenStream,
+    iterates_over: Option<&'a Ident>,
+    show_hidden_with: Option<&'a Literal>,
+    doc: &'a DocAttribute,
+    body: Body<'a>,
+    // The struct has a `__schmargs_marker` field for generics its fields don't use
+    marker: bool,
+}
+
+fn impl_schmargs(input: &SchmargsInput) -> TokenStream {
+    let SchmargsInput {
+        struct_name,
+        generics,
+        ref command_name,
+        iterates_over,
+        show_hidden_with,
+        doc,
+        body,
+        marker,
+    } = *input;
+    let description = &doc.short;
+    let long_description = &doc.long;
     let default_lifetime =
         LifetimeParam::new(Lifetime::new("'__schmargs_lifetime", Span::call_site()));
-    let generics = input.generics.clone();
     let lifetime = generics.lifetimes().next().unwrap_or(&default_lifetime);
 
     let impl_generics = if generics.lt_token.is_some() {
         let generics = crate::utils::copy_generics(
-            &generics,
+            generics,
             crate::utils::CopyGenericsBoundOption::WithBounds,
         );
         quote! { < #generics > }
@@ -291,11 +978,7 @@ pub fn schmargs_derive_impl(input: DeriveInput) -> Result<proc_macro::TokenStrea
         quote! { <#lifetime> }
     };
 
-    let string_type = if let Some(TopLevelAttribute {
-        iterates_over: Some(ref iterates_over),
-        ..
-    }) = attributes.top_level
-    {
+    let string_type = if let Some(iterates_over) = iterates_over {
         quote! { #iterates_over }
     } else {
         quote! { &#lifetime str }
@@ -304,7 +987,7 @@ pub fn schmargs_derive_impl(input: DeriveInput) -> Result<proc_macro::TokenStrea
     // Generics without the trait bounds
     let bare_generics = if generics.lt_token.is_some() {
         let inner = crate::utils::copy_generics(
-            &generics,
+            generics,
             crate::utils::CopyGenericsBoundOption::WithoutBounds,
         );
         let gen = quote! { < #inner > };
@@ -313,40 +996,72 @@ pub fn schmargs_derive_impl(input: DeriveInput) -> Result<proc_macro::TokenStrea
         quote! {}
     };
 
-    let fields = match &input.data {
-        Data::Struct(DataStruct {
-            fields: Fields::Named(fields),
-            ..
-        }) => fields.clone(),
-        _ => bail!("expected a struct with named fields"),
-    };
-
-    let args: Vec<_> = fields
-        .named
-        .iter()
-        .map(|field| {
-            let source = field.ty.span().source_text().unwrap();
-            let is_bool = source == "bool";
-
-            let first_word_of_source = source.split_whitespace().next().unwrap();
-            let is_option = first_word_of_source == "Option";
-            let is_collection = first_word_of_source == "Vec";
-
-            let attr = parse_attributes(&field.attrs).unwrap();
-            let ident = field.ident.clone().unwrap().clone();
-            Arg {
-                is_bool,
-                is_option,
-                is_collection,
-                attr,
-                ident,
+    let mut flatten_body = None;
+    let mut has_flattened = false;
+    let (parse_body, usage_body, specs, subcommand_help) = match body {
+        Body::Struct(args, groups) => {
+            flatten_body = Some(impl_flatten_body(
+                &string_type,
+                lifetime,
+                args,
+                groups,
+                marker,
+            ));
+            let mut parse_body = impl_parse_body(
+                &string_type,
+                lifetime,
+                args,
+                groups,
+                &quote! { Self },
+                &quote! { <Self as ::schmargs::Schmargs<#lifetime>>::ARGS },
+                marker,
+            );
+            has_flattened = args.iter().any(|a| a.kind() == ArgKind::Flatten);
+            if has_flattened {
+                // Make sure flags don't conflict
+                parse_body = quote! {
+                    #[allow(clippy::let_unit_value)]
+                    let () = <Self as ::schmargs::utils::Flatten<#lifetime>>::VALIDATE;
+                    #parse_body
+                };
             }
-        })
-        .collect();
+            let arg_specs = impl_arg_specs(&string_type, lifetime, args);
+            let help_body = impl_help_body(lifetime);
+            (
+                parse_body,
+                impl_usage_body(command_name, lifetime, args, groups),
+                quote! {
+                    const ARGS: &'static [::schmargs::spec::ArgSpec] = #arg_specs;
+                },
+                quote! {
+                    let _ = path;
+                    let mut f = ::schmargs::utils::HelpWriter::new(f, width);
+                    let __schmargs_long = kind == ::schmargs::utils::HelpKind::Long;
+                    let __schmargs_hidden = kind == ::schmargs::utils::HelpKind::All;
+                    #help_body
+                },
+            )
+        }
+        Body::Enum(commands) => {
+            let types = command_types(struct_name, &bare_generics, commands);
+            let command_specs = impl_command_specs(lifetime, commands, &types);
+            (
+                impl_command_parse_body(lifetime, commands, &types),
+                impl_command_usage_body(command_name, commands),
+                quote! {
+                    const SUBCOMMANDS: &'static [::schmargs::spec::CommandSpec] = #command_specs;
+                },
+                impl_command_subcommand_help(lifetime, commands, &types),
+            )
+        }
+    };
 
-    let help_body = impl_help_body(&args);
-    let parse_body = impl_parse_body(&string_type, &args);
-    let usage_body = impl_usage_body(&command_name, &args);
+    let flags = match show_hidden_with {
+        Some(flag) if flatten_body.is_some() => quote! {
+            const SHOW_HIDDEN_WITH: ::core::option::Option<&'static str> = ::core::option::Option::Some(#flag);
+        },
+        _ => quote! {},
+    };
 
     let mut gen = quote! {
         impl #impl_generics ::schmargs::Schmargs<#lifetime> for #struct_name #bare_generics {
@@ -358,9 +1073,43 @@ pub fn schmargs_derive_impl(input: DeriveInput) -> Result<proc_macro::TokenStrea
             };
             const VERSION: &'static str = env!("CARGO_PKG_VERSION");
             const DESCRIPTION: &'static str = #description;
+            const LONG_DESCRIPTION: &'static str = #long_description;
+            #flags
+            #specs
 
-            fn write_help_with_min_indent(mut f: impl ::core::fmt::Write, mut min_indent: usize) -> Result<usize, ::core::fmt::Error> {
-                #help_body
+            fn write_help_with_min_indent(f: impl ::core::fmt::Write, min_indent: usize) -> Result<usize, ::core::fmt::Error> {
+                Self::write_help_with_width(f, min_indent, ::schmargs::utils::NO_WRAP)
+            }
+
+            fn write_long_help_with_min_indent(f: impl ::core::fmt::Write, min_indent: usize) -> Result<usize, ::core::fmt::Error> {
+                Self::write_long_help_with_width(f, min_indent, ::schmargs::utils::NO_WRAP)
+            }
+
+            fn write_help_all_with_min_indent(f: impl ::core::fmt::Write, min_indent: usize) -> Result<usize, ::core::fmt::Error> {
+                Self::write_help_all_with_width(f, min_indent, ::schmargs::utils::NO_WRAP)
+            }
+
+            fn write_help_with_width(f: impl ::core::fmt::Write, min_indent: usize, width: usize) -> Result<usize, ::core::fmt::Error> {
+                Ok(Self::write_subcommand_help(&[], &Self::NAME, f, min_indent, width, ::schmargs::utils::HelpKind::Short)?.0)
+            }
+
+            fn write_long_help_with_width(f: impl ::core::fmt::Write, min_indent: usize, width: usize) -> Result<usize, ::core::fmt::Error> {
+                Ok(Self::write_subcommand_help(&[], &Self::NAME, f, min_indent, width, ::schmargs::utils::HelpKind::Long)?.0)
+            }
+
+            fn write_help_all_with_width(f: impl ::core::fmt::Write, min_indent: usize, width: usize) -> Result<usize, ::core::fmt::Error> {
+                Ok(Self::write_subcommand_help(&[], &Self::NAME, f, min_indent, width, ::schmargs::utils::HelpKind::All)?.0)
+            }
+
+            fn write_subcommand_help(
+                path: &[u16],
+                name: &dyn ::core::fmt::Display,
+                f: impl ::core::fmt::Write,
+                mut min_indent: usize,
+                width: usize,
+                kind: ::schmargs::utils::HelpKind,
+            ) -> Result<(usize, bool), ::core::fmt::Error> {
+                #subcommand_help
             }
 
             fn parse(args: impl ::core::iter::Iterator<Item = #string_type >) -> ::core::result::Result<Self, ::schmargs::SchmargsError<#string_type>> {
@@ -369,210 +1118,1199 @@ pub fn schmargs_derive_impl(input: DeriveInput) -> Result<proc_macro::TokenStrea
         }
     };
 
+    if let Some(flatten_body) = flatten_body {
+        gen.extend(quote! {
+            impl #impl_generics ::schmargs::utils::Flatten<#lifetime> for #struct_name #bare_generics {
+                #flatten_body
+            }
+        });
+        // Check the flags of flattened fields when the crate is checked, rather than when
+        // `parse` is instantiated, unless there are type parameters to instantiate them with
+        if has_flattened
+            && generics.type_params().next().is_none()
+            && generics.const_params().next().is_none()
+        {
+            let lifetimes = generics.lifetimes().map(|_| quote! { 'static });
+            gen.extend(quote! {
+                const _: () = <#struct_name<#(#lifetimes),*> as ::schmargs::utils::Flatten<'static>>::VALIDATE;
+            });
+        }
+    }
+
+    gen.extend(impl_wrapper_markers(
+        struct_name,
+        &impl_generics,
+        &bare_generics,
+        show_hidden_with,
+        body,
+    ));
+
     // Allow showing help with `println!("{args}")
     gen.extend(quote! {
         impl #impl_generics ::core::fmt::Display for #struct_name #bare_generics {
             fn fmt(&self, __schmargs_formatter: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
-                Self::write_help_with_min_indent(__schmargs_formatter, 0)?;
+                Self::write_help_with_width(__schmargs_formatter, 0, ::schmargs::utils::default_help_width())?;
                 Ok(())
             }
         }
     });
 
-    Ok(gen.into())
+    gen
 }
 
-fn impl_parse_body(string_type: &TokenStream, args: &[Arg]) -> TokenStream {
-    let mut body = quote! {
-        let mut args = ::schmargs::utils::DumbIterator::from_args(args);
+// Let the type be wrapped by wrappers whose flags it doesn't use, so a flag that would shadow a
+// wrapper's is reported when the crate is checked. The flags of flattened fields are left to the
+// bounds
+fn impl_wrapper_markers(
+    struct_name: &Ident,
+    impl_generics: &TokenStream,
+    bare_generics: &TokenStream,
+    show_hidden_with: Option<&Literal>,
+    body: Body,
+) -> TokenStream {
+    let (shorts, longs, bounded) = match body {
+        Body::Struct(args, _) => {
+            let shorts: Vec<char> = args
+                .iter()
+                .flat_map(|arg| arg.short().into_iter().chain(arg.short_aliases(false)))
+                .map(|short| unescape(&short).chars().next().unwrap())
+                .collect();
+            let longs: Vec<String> = args
+                .iter()
+                .flat_map(|arg| {
+                    arg.long().into_iter().chain(
+                        arg.long_aliases(false)
+                            .into_iter()
+                            .map(|long| format!("--{long}")),
+                    )
+                })
+                .chain(show_hidden_with.map(unescape))
+                .collect();
+            // Prefixed long flags of flattened fields can't be the same as a wrapper's
+            let bounded: Vec<(TokenStream, bool)> = args
+                .iter()
+                .filter(|arg| arg.kind() == ArgKind::Flatten)
+                .map(|arg| (arg.ty.to_token_stream(), arg.prefix().is_empty()))
+                .collect();
+            (shorts, longs, bounded)
+        }
+        // Subcommands may use the flags of wrappers for their own arguments
+        Body::Enum(_) => (Vec::new(), Vec::new(), Vec::new()),
     };
 
-    for arg in args {
-        let ident = &arg.unique_ident();
-        body.extend(quote! {
-            #[allow(non_snake_case)]
-            let mut #ident =
-        });
-        body.extend(match arg.kind() {
-            ArgKind::Flag => {
-                if let Some(default) = arg.default_value() {
-                    quote! {
-                        #default;
-                    }
-                } else {
-                    quote! {
-                        false;
-                    }
-                }
-            }
-            ArgKind::Positional | ArgKind::Option => {
-                if let Some(default) = arg.default_value() {
-                    quote! {
-                        Some(#default);
-                    }
-                } else {
-                    quote! {
-                        ::schmargs::SchmargsField::<#string_type>::as_option();
-                    }
-                }
-            }
-        });
+    let markers = [
+        ('h', "--help", quote! { NoHelpShort }, quote! { NoHelpLong }),
+        (
+            'v',
+            "--version",
+            quote! { NoVersionShort },
+            quote! { NoVersionLong },
+        ),
+    ];
+    // The bounds are higher-ranked, so they aren't checked for types without generics until the
+    // type is wrapped
+    let mut gen = quote! {};
+    for (short, long, short_marker, long_marker) in markers {
+        if !shorts.contains(&short) {
+            let types = bounded.iter().map(|(ty, _)| ty);
+            gen.extend(quote! {
+                impl #impl_generics ::schmargs::utils::#short_marker for #struct_name #bare_generics
+                where
+                    #(for<'__schmargs_bound> #types: ::schmargs::utils::#short_marker,)*
+                {}
+            });
+        }
+        if !longs.iter().any(|l| l == long) {
+            let types = bounded.iter().filter(|(_, bound)| *bound).map(|(ty, _)| ty);
+            gen.extend(quote! {
+                impl #impl_generics ::schmargs::utils::#long_marker for #struct_name #bare_generics
+                where
+                    #(for<'__schmargs_bound> #types: ::schmargs::utils::#long_marker,)*
+                {}
+            });
+        }
     }
+    gen
+}
 
-    let short_flag_match_body = {
-        let mut body: TokenStream = Default::default();
-        for arg in args
-            .iter()
-            .filter(|a| a.kind() == ArgKind::Flag || a.kind() == ArgKind::Option)
-        {
-            let ident = &arg.unique_ident();
-            if let Some(short) = arg.short() {
-                body.extend(quote! { #short =>});
-                if arg.kind() == ArgKind::Flag {
-                    body.extend(quote! { {
-                            #ident = true;
-                        },
-                    });
-                } else {
-                    body.extend(quote! { {
-                                match args.next() {
-                                    Some(::schmargs::utils::DumbArgument::Positional(value)) => {
-                                        #ident = Some(::schmargs::SchmargsField::<#string_type>::parse_str(value)?);
-                                    },
-                                    _=> {return Err(::schmargs::SchmargsError::ExpectedValue(stringify!(#ident)));}
-                                }
-                            },
-                        });
-                }
+// Name of the hidden struct that holds the arguments of a struct variant
+fn variant_struct_name(enum_name: &Ident, command: &Command) -> Ident {
+    Ident::new(
+        &format!("__Schmargs{enum_name}{}", command.ident),
+        Span::call_site(),
+    )
+}
+
+// Hidden struct that holds the arguments of a struct variant, e.g. `Add { force: bool }`, so the
+// variant has its own usage and help text. It's generic over the same parameters as the enum
+fn impl_variant_struct(
+    enum_name: &Ident,
+    generics: &Generics,
+    command_name: &TokenStream,
+    iterates_over: Option<&Ident>,
+    command: &Command,
+    args: &[Arg],
+) -> TokenStream {
+    let struct_name = variant_struct_name(enum_name, command);
+    let members = args.iter().map(|arg| &arg.member);
+    let types = args.iter().map(|arg| &arg.ty);
+    let params =
+        crate::utils::copy_generics(generics, crate::utils::CopyGenericsBoundOption::WithBounds);
+    let lifetimes = generics.lifetimes().map(|param| &param.lifetime);
+    let type_params = generics.type_params().map(|param| &param.ident);
+    let name = command.name();
+
+    let mut gen = quote! {
+        #[doc(hidden)]
+        struct #struct_name<#params> {
+            #(#members: #types,)*
+            __schmargs_marker: ::core::marker::PhantomData<(#(&#lifetimes (),)* #(*const #type_params,)*)>,
+        }
+    };
+    gen.extend(impl_schmargs(&SchmargsInput {
+        struct_name: &struct_name,
+        generics,
+        command_name: quote! { concat!(#command_name, " ", #name) },
+        iterates_over,
+        show_hidden_with: None,
+        doc: &command.attr.doc,
+        body: Body::Struct(args, command.attr.groups()),
+        marker: true,
+    }));
+    gen
+}
+
+// Schmargs type of each subcommand: the wrapped type of newtype variants, and the hidden struct
+// of struct variants
+fn command_types(
+    enum_name: &Ident,
+    bare_generics: &TokenStream,
+    commands: &[Command],
+) -> Vec<TokenStream> {
+    commands
+        .iter()
+        .map(|command| match &command.kind {
+            CommandKind::Struct(_) => {
+                let struct_name = variant_struct_name(enum_name, command);
+                quote! { #struct_name #bare_generics }
             }
+            CommandKind::Newtype(ty) => quote! { #ty },
+        })
+        .collect()
+}
+
+// Parse the fields of a struct or variant. Fields with errors are skipped, and their errors are
+// added to `errors`
+fn parse_fields(fields: &Fields, env_prefix: &str, errors: &mut Errors) -> Vec<Arg> {
+    let args: Vec<Arg> = fields
+        .iter()
+        .enumerate()
+        .filter_map(|(index, field)| errors.check(parse_field(field, index, env_prefix)))
+        .collect();
+
+    // Arguments can only relate to their siblings
+    for arg in &args {
+        if arg.kind() == ArgKind::Flatten && arg.relations().next().is_some() {
+            errors.push(syn::Error::new_spanned(
+                &arg.ident,
+                format!(
+                    "Flattened field `{}` can't relate to other arguments",
+                    arg.ident
+                ),
+            ));
+        }
+        for other in arg.relations() {
+            let message = match args.iter().find(|a| a.ident == *other) {
+                Some(other) if other.kind() != ArgKind::Flatten => continue,
+                Some(_) => format!("`{}` can't relate to flattened field `{other}`", arg.ident),
+                None => format!("`{}` relates to unknown argument `{other}`", arg.ident),
+            };
+            errors.push(syn::Error::new_spanned(other, message));
         }
+    }
 
-        body
-    };
+    // Flags must be unique. Flags of flattened fields are checked by `Flatten::VALIDATE`
+    let mut flags: Vec<(String, &Ident)> = Vec::new();
+    for arg in &args {
+        let shorts = arg
+            .short()
+            .into_iter()
+            .chain(arg.short_aliases(false))
+            .map(|short| format!("-{}", unescape(&short)));
+        let longs = [arg.long(), arg.negated_long()]
+            .into_iter()
+            .flatten()
+            .chain(
+                arg.long_aliases(false)
+                    .into_iter()
+                    .map(|long| format!("--{long}")),
+            );
+        for flag in shorts.chain(longs) {
+            if let Some((_, owner)) = flags.iter().find(|(other, _)| *other == flag) {
+                errors.push(syn::Error::new_spanned(
+                    &arg.ident,
+                    format!("Flag `{flag}` is already used by `{owner}`"),
+                ));
+            } else {
+                flags.push((flag, &arg.ident));
+            }
+        }
+    }
 
-    let match_body = {
-        let mut body: TokenStream = Default::default();
-        for arg in args
-            .iter()
-            .filter(|a| a.kind() == ArgKind::Flag || a.kind() == ArgKind::Option)
+    // Arguments that can be left out for another one need a value to fall back on
+    for arg in &args {
+        let required_unless_present = arg
+            .attr
+            .arg
+            .as_ref()
+            .is_some_and(|attr| !attr.required_unless_present.is_empty());
+        if required_unless_present
+            && matches!(arg.kind(), ArgKind::Option | ArgKind::Positional)
+            && !arg.is_option
+            && arg.default_value().is_none()
+        {
+            errors.push(syn::Error::new_spanned(
+                &arg.ident,
+                format!(
+                    "`required_unless_present` needs `{}` to be an `Option` or have a `default_value`",
+                    arg.ident
+                ),
+            ));
+        }
+    }
+
+    // A required positional argument can't follow an optional or variadic one, which would take
+    // its value
+    let mut first_optional: Option<(&Ident, &str)> = None;
+    for arg in args.iter().filter(|a| a.kind() == ArgKind::Positional) {
+        let kind = if arg.is_collection {
+            Some("variadic")
+        } else if arg.is_option || arg.default_value().is_some() || arg.env.is_some() {
+            Some("optional")
+        } else {
+            None
+        };
+        match (kind, first_optional) {
+            (None, Some((other, other_kind))) => errors.push(syn::Error::new_spanned(
+                &arg.ident,
+                format!(
+                    "Required positional argument `{}` can't follow {other_kind} argument `{other}`",
+                    arg.ident
+                ),
+            )),
+            (Some(kind), None) => first_optional = Some((&arg.ident, kind)),
+            _ => {}
+        }
+    }
+
+    args
+}
+
+fn parse_field(field: &syn::Field, index: usize, env_prefix: &str) -> Result<Arg> {
+    let (ident, member) = match &field.ident {
+        Some(ident) => (ident.clone(), Member::Named(ident.clone())),
+        None => (
+            Ident::new(&format!("arg{index}"), field.ty.span()),
+            Member::Unnamed(Index {
+                index: index as u32,
+                span: field.ty.span(),
+            }),
+        ),
+    };
+    let source = field
+        .ty
+        .span()
+        .source_text()
+        .unwrap_or_else(|| field.ty.to_token_stream().to_string());
+    let is_bool = source == "bool";
+
+    let first_word_of_source = source.split_whitespace().next().unwrap_or_default();
+    let is_option = first_word_of_source == "Option";
+    let is_collection = first_word_of_source == "Vec";
+
+    let attr = parse_attributes(&field.attrs)?;
+    if let Some(arg) = &attr.arg {
+        let positional = arg.short.is_none() && arg.long.is_none() && !arg.flatten;
+        if field.ident.is_none() && !positional {
+            bail!(
+                field,
+                "Fields of tuple structs can only be positional arguments"
+            );
+        }
+        if arg.prefix.is_some() && !arg.flatten {
+            bail!(ident, "`prefix` can only be used with `flatten`");
+        }
+        if arg.flatten && (arg.short.is_some() || arg.long.is_some()) {
+            bail!(ident, "`flatten` can not be used with `short` or `long`");
+        }
+        if arg.flatten && arg.hide {
+            bail!(ident, "`flatten` can not be used with `hide`");
+        }
+        if arg.negatable && (!is_bool || arg.long.is_none() || arg.action.is_some()) {
+            bail!(
+                ident,
+                "`negatable` can only be used on `bool` flags with `long`"
+            );
+        }
+        if let Some(action) = &arg.action {
+            if action != "count" {
+                bail!(action, "Unknown action `{action}` (expected `count`)");
+            }
+            if arg.short.is_none() && arg.long.is_none() {
+                bail!(ident, "`action = count` requires `short` or `long`");
+            }
+        }
+    }
+    let env = match &attr.arg {
+        Some(ArgAttribute {
+            env: Some(Some(env)),
+            ..
+        }) => Some(unescape(env)),
+        Some(ArgAttribute {
+            env: Some(None), ..
+        }) => Some(String::from(env_prefix) + &ident.to_string().to_uppercase()),
+        _ => None,
+    };
+    let arg = Arg {
+        member,
+        ty: field.ty.clone(),
+        is_bool,
+        is_option,
+        is_collection,
+        env,
+        attr,
+        ident,
+    };
+    let ident = &arg.ident;
+    if let Some(attr) = &arg.attr.arg {
+        let has_aliases = !attr.aliases.is_empty()
+            || !attr.visible_aliases.is_empty()
+            || !attr.short_aliases.is_empty()
+            || !attr.visible_short_aliases.is_empty();
+        if has_aliases && matches!(arg.kind(), ArgKind::Positional | ArgKind::Flatten) {
+            bail!(ident, "Aliases can only be used on flags and options");
+        }
+        let has_constraints = attr.range.is_some()
+            || attr.possible_values.is_some()
+            || attr.min_len.is_some()
+            || attr.max_len.is_some();
+        if has_constraints && !matches!(arg.kind(), ArgKind::Option | ArgKind::Positional) {
+            bail!(
+                ident,
+                "Constraints can only be used on options and positional arguments"
+            );
+        }
+        if attr.value_parser.is_some()
+            && !matches!(arg.kind(), ArgKind::Option | ArgKind::Positional)
         {
-            let ident = &arg.unique_ident();
-            if let Some(long) = arg.long() {
-                body.extend(
-                    quote! { ::schmargs::utils::DumbArgument::LongFlag(__schmargs_throwaway) if ::core::convert::AsRef::<str>::as_ref(&__schmargs_throwaway) == #long =>},
+            bail!(
+                ident,
+                "`value_parser` can only be used on options and positional arguments"
+            );
+        }
+        if let Some(hint) = &attr.value_hint {
+            if !matches!(arg.kind(), ArgKind::Option | ArgKind::Positional) {
+                bail!(
+                    hint,
+                    "`value_hint` can only be used on options and positional arguments"
                 );
-                if arg.kind() == ArgKind::Flag {
-                    body.extend(quote! { {
-                            #ident = true;
-                        },
-                    });
-                } else {
-                    body.extend(quote! { {
-                                match args.next() {
-                                    Some(::schmargs::utils::DumbArgument::Positional(value)) => {
-                                        #ident = Some(::schmargs::SchmargsField::<#string_type>::parse_str(value)?);
-                                    },
-                                    _=> {return Err(::schmargs::SchmargsError::ExpectedValue(stringify!(#ident)));}
-                                }
-                            },
-                        });
-                }
             }
+            if value_hint_variant(hint).is_none() {
+                bail!(
+                    hint,
+                    "Unknown value hint `{hint}` (expected `path`, `file`, `dir`, `hostname`, `username`, or `command`)"
+                );
+            }
+        }
+        if (attr.min_len.is_some() || attr.max_len.is_some()) && !arg.is_collection {
+            bail!(
+                ident,
+                "`min_len` and `max_len` can only be used on collections"
+            );
         }
+    }
+    if arg.env.is_some() && !matches!(arg.kind(), ArgKind::Option | ArgKind::Positional) {
+        bail!(
+            ident,
+            "`env` can only be used on options and positional arguments"
+        );
+    }
+    Ok(arg)
+}
 
-        let (num, positional): (Vec<usize>, Vec<proc_macro2::Ident>) = args
-            .iter()
-            .filter(|a| a.kind() == ArgKind::Positional)
-            .map(|a| a.unique_ident().clone())
-            .enumerate()
-            .unzip();
-        if !positional.is_empty() {
-            let (num, positional) = (num.into_iter(), positional.into_iter());
-            body.extend(quote! {
-                ::schmargs::utils::DumbArgument::Positional(value) => {
-                    match pos_count {
-                    #(
-                        #num => {#positional = Some(::schmargs::SchmargsField::<#string_type>::parse_it(value, (&mut args).map(|v|v.into_inner()))?);},
-                    )*
-                        _ => {return ::core::result::Result::Err(::schmargs::SchmargsError::UnexpectedValue(value));}
-                    }
-                    pos_count += 1;
-                },
-            });
-        } else {
-            body.extend(quote! {
-                ::schmargs::utils::DumbArgument::Positional(val) => {
-                    ::core::result::Result::Err(::schmargs::SchmargsError::UnexpectedValue(val))?;
-                },
-            });
+// Find a sibling argument by its ident
+fn find_arg<'a>(args: &'a [Arg], ident: &Ident) -> &'a Arg {
+    args.iter()
+        .find(|a| a.ident == *ident)
+        .expect("Bug: expected argument to exist")
+}
+
+fn validate_groups(args: &[Arg], groups: &[GroupAttribute]) -> Result<()> {
+    let mut errors = Errors::default();
+    for group in groups {
+        for ident in &group.args {
+            let message = match args.iter().find(|a| a.ident == *ident) {
+                None => format!("Group `{}` contains unknown argument `{ident}`", group.name),
+                Some(arg)
+                    if !matches!(arg.kind(), ArgKind::Flag | ArgKind::Count | ArgKind::Option) =>
+                {
+                    String::from("Groups can only contain flags and options")
+                }
+                Some(_) if groups.iter().filter(|g| g.args.contains(ident)).count() > 1 => {
+                    format!("`{ident}` can't be in more than one group")
+                }
+                Some(_) => continue,
+            };
+            errors.push(syn::Error::new_spanned(ident, message));
+        }
+    }
+    errors.finish()
+}
+
+// Parse the variants of an enum. Variants with errors are skipped, and their errors are added to
+// `errors`
+fn parse_variants(data: &DataEnum, env_prefix: &str, errors: &mut Errors) -> Vec<Command> {
+    let mut commands = Vec::new();
+    for variant in &data.variants {
+        let Some(attr) = errors.check(parse_attributes(&variant.attrs)) else {
+            continue;
         };
-        body.extend(quote! {
-            ::schmargs::utils::DumbArgument::LongFlag(val) => {
-                ::core::result::Result::Err(::schmargs::SchmargsError::NoSuchLongFlag(val))?;
+        let kind = match &variant.fields {
+            Fields::Named(_) => {
+                let args = parse_fields(&variant.fields, env_prefix, errors);
+                errors.check(validate_groups(&args, attr.groups()));
+                CommandKind::Struct(args)
             }
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                CommandKind::Newtype(Box::new(fields.unnamed[0].ty.clone()))
+            }
+            _ => {
+                errors.push(syn::Error::new_spanned(
+                    &variant.ident,
+                    format!(
+                        "expected subcommand variant `{}` to have named fields or wrap a single type",
+                        variant.ident
+                    ),
+                ));
+                continue;
+            }
+        };
+        commands.push(Command {
+            attr,
+            ident: variant.ident.clone(),
+            kind,
         });
+    }
+    commands
+}
 
-        body
+// Expression that parses `value` into the type of an argument. `rest` evaluates to an iterator
+// over the values that follow, which are taken by collections
+fn impl_parse_value(
+    string_type: &TokenStream,
+    arg: &Arg,
+    rest: Option<TokenStream>,
+) -> TokenStream {
+    let Some(ArgAttribute {
+        value_parser: Some(parser),
+        ..
+    }) = &arg.attr.arg
+    else {
+        return match rest {
+            Some(rest) => quote! {
+                ::schmargs::SchmargsField::<#string_type>::parse_it(value, #rest)?
+            },
+            None => quote! {
+                ::schmargs::SchmargsField::<#string_type>::parse_str(value)?
+            },
+        };
     };
 
-    let return_body = {
-        let mut body: TokenStream = Default::default();
+    let original_ident = &arg.ident;
+    let map_err = quote! {
+        .map_err(|err| ::schmargs::SchmargsError::ValueParserFailed(
+            stringify!(#original_ident),
+            ::schmargs::ValueParserError::new(err),
+        ))?
+    };
+    if arg.is_collection {
+        let values = match rest {
+            Some(rest) => quote! { ::core::iter::once(value).chain(#rest) },
+            None => quote! { ::schmargs::utils::StringLike::split_commas(value) },
+        };
+        quote! {
+            #values.map(#parser).collect::<::core::result::Result<_, _>>()#map_err
+        }
+    } else if arg.is_option {
+        quote! { Some(#parser(value)#map_err) }
+    } else {
+        quote! { #parser(value)#map_err }
+    }
+}
 
-        for arg in args {
-            let original_ident = &arg.ident;
-            let unique_ident = &arg.unique_ident();
-            body.extend(match arg.kind() {
-                ArgKind::Flag => quote! {
-                    #original_ident: #unique_ident,
-                },
-                ArgKind::Positional | ArgKind::Option => quote! {
-                    #original_ident: #unique_ident.ok_or(
-                        ::schmargs::SchmargsError::ExpectedValue(stringify!(#original_ident))
-                    )?,
-                },
-            });
+// Expression that evaluates to `Some(None)` if the argument is an `Option`, and `None` otherwise
+fn impl_as_option(string_type: &TokenStream, arg: &Arg) -> TokenStream {
+    match &arg.attr.arg {
+        // The type of the argument might not implement `SchmargsField`
+        Some(ArgAttribute {
+            value_parser: Some(_),
+            ..
+        }) => {
+            if arg.is_option {
+                quote! { Some(None) }
+            } else {
+                quote! { None }
+            }
         }
+        _ => quote! { ::schmargs::SchmargsField::<#string_type>::as_option() },
+    }
+}
 
-        body
+// Code fragments that parse a set of arguments. These are shared by the parse loop of
+// `Schmargs::parse` and the methods of `Flatten`
+struct ParserFragments {
+    // Tuple holding the state of each argument
+    init: TokenStream,
+    // Pattern that binds the state of each argument to its unique ident
+    bindings: TokenStream,
+    // Block that parses `__schmargs_long`. Evaluates to false if there's no such flag
+    long: TokenStream,
+    // Block that parses `__schmargs_short`. Evaluates to false if there's no such flag
+    short: TokenStream,
+    // Block that parses `__schmargs_value` at `__schmargs_position`. Evaluates to the value if
+    // there's no such positional argument
+    positional: TokenStream,
+    // Checks of the relations between arguments, done before finishing
+    checks: TokenStream,
+    // Struct fields, constructed from the state of each argument
+    finish: TokenStream,
+}
+
+// `<ty as Flatten<'lifetime>>`
+fn flatten_trait(lifetime: &LifetimeParam, arg: &Arg) -> TokenStream {
+    let ty = &arg.ty;
+    quote! { <#ty as ::schmargs::utils::Flatten<#lifetime>> }
+}
+
+// Number of positional arguments before `arg`, as an expression
+fn positional_offset(lifetime: &LifetimeParam, args: &[Arg], arg: &Arg) -> TokenStream {
+    let before = args.iter().take_while(|a| a.ident != arg.ident);
+    let count = before
+        .clone()
+        .filter(|a| a.kind() == ArgKind::Positional)
+        .count();
+    let flattened = before
+        .filter(|a| a.kind() == ArgKind::Flatten)
+        .map(|a| flatten_trait(lifetime, a));
+    quote! { #count #(+ #flattened::POSITIONALS)* }
+}
+
+fn impl_parser_fragments(
+    string_type: &TokenStream,
+    lifetime: &LifetimeParam,
+    args: &[Arg],
+    groups: &[GroupAttribute],
+) -> ParserFragments {
+    let idents: Vec<_> = args.iter().map(|arg| arg.unique_ident()).collect();
+
+    let init = args.iter().map(|arg| match arg.kind() {
+        ArgKind::Flag => arg.default_value().unwrap_or_else(|| quote! { false }),
+        ArgKind::Count => arg.default_value().unwrap_or_else(|| quote! { 0 }),
+        // Fall back on the environment variable and default value when finishing, so we know
+        // which arguments were passed
+        ArgKind::Positional | ArgKind::Option => quote! { None },
+        ArgKind::Flatten => {
+            let flatten = flatten_trait(lifetime, arg);
+            quote! { #flatten::init() }
+        }
+    });
+
+    // Code that sets the value of a flag or option. `inline_value` is an expression that
+    // evaluates to the value passed with the flag itself, e.g. `--name=value`
+    let set_value = |arg: &Arg, inline_value: TokenStream| {
+        let ident = arg.unique_ident();
+        let parse_value = impl_parse_value(string_type, arg, None);
+        if arg.kind() == ArgKind::Flag {
+            quote! {
+                *#ident = true;
+            }
+        } else if arg.kind() == ArgKind::Count {
+            let ty = &arg.ty;
+            quote! {
+                *#ident = <#ty>::saturating_add(*#ident, 1);
+            }
+        } else {
+            let flag = arg.flag_name();
+            quote! {
+                let value = match #inline_value {
+                    Some(value) => value,
+                    None => match args.next() {
+                        Some(::schmargs::utils::DumbArgument::Positional(value)) => value,
+                        _ => {
+                            return ::core::result::Result::Err(::schmargs::SchmargsError::ExpectedValue(#flag));
+                        }
+                    }
+                };
+                *#ident = Some(#parse_value);
+            }
+        }
     };
 
-    body.extend(quote! {
+    let mut long: TokenStream = Default::default();
+    let mut short: TokenStream = Default::default();
+    for arg in args
+        .iter()
+        .filter(|a| matches!(a.kind(), ArgKind::Flag | ArgKind::Count | ArgKind::Option))
+    {
+        let longs: Vec<_> = arg
+            .long()
+            .map(|long| {
+                long.strip_prefix("--")
+                    .expect("Bug: expected long flag")
+                    .to_string()
+            })
+            .into_iter()
+            .chain(arg.long_aliases(false))
+            .collect();
+        if !longs.is_empty() {
+            let set_value = set_value(arg, quote! { __schmargs_long_value.take() });
+            long.extend(quote! {
+                if #(__schmargs_long == #longs)||* {
+                    #set_value
+                    break '__schmargs_long true;
+                }
+            });
+        }
+        if let Some(flag) = arg.negated_long() {
+            let ident = arg.unique_ident();
+            let flag = flag.strip_prefix("--").expect("Bug: expected long flag");
+            long.extend(quote! {
+                if __schmargs_long == #flag {
+                    *#ident = false;
+                    break '__schmargs_long true;
+                }
+            });
+        }
+        let shorts: Vec<_> = arg
+            .short()
+            .into_iter()
+            .chain(arg.short_aliases(false))
+            .collect();
+        if !shorts.is_empty() {
+            let set_value = set_value(arg, quote! { __schmargs_cluster.take_value() });
+            short.extend(quote! {
+                if #(__schmargs_short == #shorts)||* {
+                    #set_value
+                    break '__schmargs_short true;
+                }
+            });
+        }
+    }
 
-        let mut pos_count = 0;
+    let mut positional: TokenStream = Default::default();
+    for arg in args {
+        let ident = arg.unique_ident();
+        let offset = positional_offset(lifetime, args, arg);
+        match arg.kind() {
+            ArgKind::Positional => {
+                let parse_value = impl_parse_value(
+                    string_type,
+                    arg,
+                    Some(quote! { args.by_ref().map(::schmargs::utils::DumbArgument::into_inner) }),
+                );
+                positional.extend(quote! {
+                    if __schmargs_position == #offset {
+                        let value = __schmargs_value;
+                        *#ident = Some(#parse_value);
+                        break '__schmargs_positional None;
+                    }
+                })
+            }
+            ArgKind::Flatten => {
+                let flatten = flatten_trait(lifetime, arg);
+                let prefix = arg.prefix();
+                long.extend(quote! {
+                    if let Some(__schmargs_long) = __schmargs_long.strip_prefix(#prefix) {
+                        if #flatten::parse_long(#ident, __schmargs_long, __schmargs_long_value, args)? {
+                            break '__schmargs_long true;
+                        }
+                    }
+                });
+                short.extend(quote! {
+                    if #flatten::parse_short(#ident, __schmargs_short, __schmargs_cluster, args)? {
+                        break '__schmargs_short true;
+                    }
+                });
+                positional.extend(quote! {
+                    if __schmargs_position < #offset + #flatten::POSITIONALS {
+                        break '__schmargs_positional #flatten::parse_positional(
+                            #ident,
+                            __schmargs_position - (#offset),
+                            __schmargs_value,
+                            args
+                        )?;
+                    }
+                });
+            }
+            ArgKind::Flag | ArgKind::Count | ArgKind::Option => {}
+        }
+    }
 
-        while let Some(arg) = args.next() {
-            match arg {
-                ::schmargs::utils::DumbArgument::ShortFlags(shorts) => {
-                    for short in AsRef::<str>::as_ref(&shorts).strip_prefix("-").expect("Bug: expected short flag here").chars() {
-                        let short: char = short;
-                        match short {
-                            #short_flag_match_body
-                            __schmargs_misc_short_flag => {
+    let mut checks: TokenStream = Default::default();
+    for arg in args {
+        let Some(attr) = &arg.attr.arg else {
+            continue;
+        };
+        if arg.relations().next().is_none() {
+            continue;
+        }
+        let name = arg.display_name();
+        let present = arg.is_present();
+        let find = |ident| find_arg(args, ident);
+        for other in &attr.requires {
+            let other = find(other);
+            let other_name = other.display_name();
+            let other_present = other.is_present();
+            checks.extend(quote! {
+                if #present && !#other_present {
+                    return ::core::result::Result::Err(::schmargs::SchmargsError::Requires(
+                        #name,
+                        #other_name,
+                    ));
+                }
+            });
+        }
+        for other in &attr.conflicts_with {
+            let other = find(other);
+            let other_name = other.display_name();
+            let other_present = other.is_present();
+            checks.extend(quote! {
+                if #present && #other_present {
+                    return ::core::result::Result::Err(::schmargs::SchmargsError::ConflictsWith(
+                        #name,
+                        #other_name,
+                    ));
+                }
+            });
+        }
+        if !attr.required_unless_present.is_empty() {
+            let others: Vec<_> = attr.required_unless_present.iter().map(find).collect();
+            let others_names = others.iter().map(|other| other.display_name());
+            let others_present = others.iter().map(|other| other.is_present());
+            checks.extend(quote! {
+                if !#present #(&& !#others_present)* {
+                    return ::core::result::Result::Err(
+                        ::schmargs::SchmargsError::RequiredUnlessPresent(
+                            #name,
+                            &[#(#others_names),*],
+                        )
+                    );
+                }
+            });
+        }
+    }
+
+    for group in groups {
+        let name = &group.name;
+        let members: Vec<_> = group
+            .args
+            .iter()
+            .map(|ident| find_arg(args, ident))
+            .collect();
+        let names: Vec<_> = members.iter().map(|arg| arg.display_name()).collect();
+        let present: Vec<_> = members.iter().map(|arg| arg.is_present()).collect();
+        if group.required {
+            checks.extend(quote! {
+                if #(!#present)&&* {
+                    return ::core::result::Result::Err(::schmargs::SchmargsError::MissingGroup(
+                        #name,
+                        &[#(#names),*],
+                    ));
+                }
+            });
+        }
+        if !group.multiple {
+            for (i, (a, a_present)) in names.iter().zip(&present).enumerate() {
+                for (b, b_present) in names.iter().zip(&present).skip(i + 1) {
+                    checks.extend(quote! {
+                        if #a_present && #b_present {
+                            return ::core::result::Result::Err(::schmargs::SchmargsError::GroupConflict(
+                                #name,
+                                #a,
+                                #b,
+                            ));
+                        }
+                    });
+                }
+            }
+        }
+    }
+
+    let finish = args.iter().map(|arg| {
+        let member = &arg.member;
+        let unique_ident = arg.unique_ident();
+        match arg.kind() {
+            ArgKind::Flag | ArgKind::Count => quote! {
+                #member: #unique_ident,
+            },
+            ArgKind::Positional | ArgKind::Option => {
+                let as_option = impl_as_option(string_type, arg);
+                let name = arg.display_name();
+                let missing = if arg.kind() == ArgKind::Option {
+                    quote! { ::schmargs::SchmargsError::MissingRequiredArgument(#name) }
+                } else {
+                    quote! { ::schmargs::SchmargsError::ExpectedValue(#name) }
+                };
+                let mut fallback = match arg.default_value() {
+                    Some(default) => default,
+                    None => quote! {
+                        #as_option.ok_or(#missing)?
+                    },
+                };
+                if let Some(env) = &arg.env {
+                    let parse_value = impl_parse_value(string_type, arg, None);
+                    fallback = quote! {
+                        match ::schmargs::utils::env_var::<#string_type>(#env) {
+                            Some(value) => #parse_value,
+                            None => #fallback,
+                        }
+                    };
+                }
+                let value = quote! {
+                    match #unique_ident {
+                        Some(value) => value,
+                        None => #fallback,
+                    }
+                };
+                let constraints = arg.constraints();
+                if constraints.is_empty() {
+                    quote! {
+                        #member: #value,
+                    }
+                } else {
+                    let name = arg.display_name();
+                    let checks = constraints.into_iter().map(|(constraint, check)| {
+                        quote! {
+                            if !(#check) {
                                 return ::core::result::Result::Err(
-                                    ::schmargs::SchmargsError::NoSuchShortFlag(
-                                            __schmargs_misc_short_flag
-                                    )
+                                    ::schmargs::SchmargsError::ConstraintViolation(#name, #constraint)
                                 );
                             }
                         }
+                    });
+                    let ty = &arg.ty;
+                    quote! {
+                        #member: {
+                            let __schmargs_value: #ty = #value;
+                            #(#checks)*
+                            __schmargs_value
+                        },
+                    }
+                }
+            }
+            ArgKind::Flatten => {
+                let flatten = flatten_trait(lifetime, arg);
+                quote! {
+                    #member: #flatten::finish(#unique_ident)?,
+                }
+            }
+        }
+    });
+
+    ParserFragments {
+        init: quote! { ( #(#init,)* ) },
+        bindings: quote! { ( #(#idents,)* ) },
+        long: quote! {
+            '__schmargs_long: {
+                #long
+                break '__schmargs_long false;
+            }
+        },
+        short: quote! {
+            '__schmargs_short: {
+                #short
+                break '__schmargs_short false;
+            }
+        },
+        positional: quote! {
+            '__schmargs_positional: {
+                #positional
+                break '__schmargs_positional Some(__schmargs_value);
+            }
+        },
+        checks,
+        finish: quote! { #(#finish)* },
+    }
+}
+
+// Initializer of the `__schmargs_marker` field, if there is one
+fn impl_finish_marker(marker: bool) -> TokenStream {
+    if marker {
+        quote! { __schmargs_marker: ::core::marker::PhantomData, }
+    } else {
+        quote! {}
+    }
+}
+
+fn impl_parse_body(
+    string_type: &TokenStream,
+    lifetime: &LifetimeParam,
+    args: &[Arg],
+    groups: &[GroupAttribute],
+    constructor: &TokenStream,
+    arg_specs: &TokenStream,
+    marker: bool,
+) -> TokenStream {
+    let finish_marker = impl_finish_marker(marker);
+    let ParserFragments {
+        init,
+        bindings,
+        long,
+        short,
+        positional,
+        checks,
+        finish,
+    } = impl_parser_fragments(string_type, lifetime, args, groups);
+
+    // Flattened fields may have a prefix, whose flags are joined with it at compile time
+    let suggestion = if args.iter().any(|a| a.kind() == ArgKind::Flatten) {
+        quote! {
+            ::schmargs::utils::suggest_prefixed_long(
+                __schmargs_long,
+                #arg_specs,
+                const { &::schmargs::utils::PrefixedLongs::new(#arg_specs) },
+            )
+        }
+    } else {
+        quote! { ::schmargs::utils::suggest_long(__schmargs_long, #arg_specs) }
+    };
+
+    quote! {
+        let mut __schmargs_state = #init;
+        #[allow(non_snake_case)]
+        let #bindings = &mut __schmargs_state;
+        let args = &mut ::schmargs::utils::DumbIterator::from_args(args);
+        let mut __schmargs_position = 0;
+
+        while let Some(arg) = args.next() {
+            match arg {
+                ::schmargs::utils::DumbArgument::ShortFlags(shorts) => {
+                    let __schmargs_cluster = &mut ::schmargs::utils::ShortCluster::new(shorts);
+                    while let Some(__schmargs_short) = __schmargs_cluster.next() {
+                        if !(#short) {
+                            return ::core::result::Result::Err(
+                                ::schmargs::SchmargsError::NoSuchShortFlag(__schmargs_short)
+                            );
+                        }
+                    }
+                },
+                ::schmargs::utils::DumbArgument::LongFlag(__schmargs_flag) => {
+                    let (__schmargs_flag, mut __schmargs_long_value) = ::schmargs::utils::split_long_flag(__schmargs_flag);
+                    let __schmargs_long_value = &mut __schmargs_long_value;
+                    let __schmargs_long: &str = AsRef::<str>::as_ref(&__schmargs_flag).strip_prefix("--").expect("Bug: expected long flag here");
+                    if !(#long) {
+                        return ::core::result::Result::Err(
+                            ::schmargs::SchmargsError::NoSuchLongFlag {
+                                suggestion: #suggestion,
+                                got: __schmargs_flag,
+                            }
+                        );
+                    }
+                    // Flags don't take the value
+                    if __schmargs_long_value.is_some() {
+                        return ::core::result::Result::Err(
+                            ::schmargs::SchmargsError::UnexpectedFlagValue(__schmargs_flag)
+                        );
+                    }
+                },
+                ::schmargs::utils::DumbArgument::Positional(__schmargs_value) => {
+                    if let Some(value) = (#positional) {
+                        return ::core::result::Result::Err(
+                            ::schmargs::SchmargsError::UnexpectedValue(value)
+                        );
                     }
+                    __schmargs_position += 1;
                 },
-                #match_body
             }
         }
 
-        Ok(Self {
-            #return_body
+        #[allow(non_snake_case)]
+        let #bindings = __schmargs_state;
+        #checks
+        Ok(#constructor {
+            #finish
+            #finish_marker
         })
+    }
+}
+
+fn impl_flatten_body(
+    string_type: &TokenStream,
+    lifetime: &LifetimeParam,
+    args: &[Arg],
+    groups: &[GroupAttribute],
+    marker: bool,
+) -> TokenStream {
+    let finish_marker = impl_finish_marker(marker);
+    let ParserFragments {
+        init,
+        bindings,
+        long,
+        short,
+        positional,
+        checks,
+        finish,
+    } = impl_parser_fragments(string_type, lifetime, args, groups);
+
+    let state = args.iter().map(|arg| {
+        let ty = &arg.ty;
+        match arg.kind() {
+            ArgKind::Flag => quote! { bool },
+            ArgKind::Count => quote! { #ty },
+            ArgKind::Positional | ArgKind::Option => quote! { Option<#ty> },
+            ArgKind::Flatten => {
+                let flatten = flatten_trait(lifetime, arg);
+                quote! { #flatten::State }
+            }
+        }
     });
+    let flattened: Vec<_> = args
+        .iter()
+        .filter(|a| a.kind() == ArgKind::Flatten)
+        .map(|a| (a.prefix(), flatten_trait(lifetime, a)))
+        .collect();
+    let flattened_prefixes: Vec<_> = flattened.iter().map(|v| &v.0).collect();
+    let flattened_traits: Vec<_> = flattened.iter().map(|v| &v.1).collect();
+
+    let num_positionals = args
+        .iter()
+        .filter(|a| a.kind() == ArgKind::Positional)
+        .count();
+    let num_hidden_positionals = args
+        .iter()
+        .filter(|a| a.kind() == ArgKind::Positional && a.is_hidden())
+        .count();
+    let usage_positionals = impl_usage_positionals(lifetime, args);
+    let usage_options = args.iter().any(Arg::is_usage_option);
+    let ungrouped_options: Vec<_> = args
+        .iter()
+        .filter(|a| {
+            matches!(a.kind(), ArgKind::Flag | ArgKind::Count | ArgKind::Option)
+                && a.group(groups).is_none()
+        })
+        .collect();
+    let help_options = ungrouped_options.iter().any(|a| !a.is_hidden());
+    let hidden_options = ungrouped_options.iter().any(|a| a.is_hidden());
+    let shorts = args.iter().flat_map(|arg| {
+        let field = arg.ident.to_string();
+        arg.short()
+            .into_iter()
+            .chain(arg.short_aliases(false))
+            .map(move |short| quote! { (#short, #field) })
+    });
+    let longs = args.iter().flat_map(|arg| {
+        let field = arg.ident.to_string();
+        [arg.long(), arg.negated_long()]
+            .into_iter()
+            .flatten()
+            .map(|long| {
+                long.strip_prefix("--")
+                    .expect("Bug: expected long flag")
+                    .to_string()
+            })
+            .chain(arg.long_aliases(false))
+            .map(move |long| quote! { (#long, #field) })
+    });
+    let flattened_fields = args
+        .iter()
+        .filter(|a| a.kind() == ArgKind::Flatten)
+        .map(|a| a.ident.to_string());
+
+    let (required_dashes, required_rest): (Vec<_>, Vec<_>) = args
+        .iter()
+        .filter(|a| a.is_required_option() && a.group(groups).is_none() && !a.is_hidden())
+        .map(Arg::required_usage)
+        .unzip();
+
+    let help = impl_help_entries(string_type, lifetime, args, groups);
+
+    quote! {
+        type State = ( #(#state,)* );
+
+        const POSITIONALS: usize = #num_positionals #(+ #flattened_traits::POSITIONALS)*;
+        const HIDDEN_POSITIONALS: usize = #num_hidden_positionals #(+ #flattened_traits::HIDDEN_POSITIONALS)*;
+        const USAGE_POSITIONALS: &'static str = #usage_positionals;
+        const USAGE_OPTIONS: bool = #usage_options #(|| #flattened_traits::USAGE_OPTIONS)*;
+        const HELP_OPTIONS: bool = #help_options #(|| #flattened_traits::HELP_OPTIONS)*;
+        const HIDDEN_OPTIONS: bool = #hidden_options #(|| #flattened_traits::HIDDEN_OPTIONS)*;
+        const FLAGS: ::schmargs::utils::FlagSet = ::schmargs::utils::FlagSet {
+            shorts: &[#(#shorts),*],
+            longs: &[#(#longs),*],
+            flattened: &[#((#flattened_prefixes, #flattened_fields, &#flattened_traits::FLAGS)),*],
+        };
+        const REQUIRED: ::schmargs::utils::RequiredOptions = ::schmargs::utils::RequiredOptions {
+            options: &[#((#required_dashes, #required_rest)),*],
+            flattened: &[#((#flattened_prefixes, &#flattened_traits::REQUIRED)),*],
+        };
+
+        // The state of a struct without fields is `()`
+        #[allow(clippy::unused_unit)]
+        fn init() -> Self::State {
+            #init
+        }
 
-    body
+        fn parse_long<__SchmargsIterator: ::core::iter::Iterator<Item = Self::Item>>(
+            __schmargs_state: &mut Self::State,
+            __schmargs_long: &str,
+            __schmargs_long_value: &mut ::core::option::Option<Self::Item>,
+            args: &mut ::schmargs::utils::DumbIterator<Self::Item, __SchmargsIterator>,
+        ) -> ::core::result::Result<bool, ::schmargs::SchmargsError<Self::Item>> {
+            #[allow(non_snake_case)]
+            let #bindings = __schmargs_state;
+            Ok(#long)
+        }
+
+        fn parse_short<__SchmargsIterator: ::core::iter::Iterator<Item = Self::Item>>(
+            __schmargs_state: &mut Self::State,
+            __schmargs_short: char,
+            __schmargs_cluster: &mut ::schmargs::utils::ShortCluster<Self::Item>,
+            args: &mut ::schmargs::utils::DumbIterator<Self::Item, __SchmargsIterator>,
+        ) -> ::core::result::Result<bool, ::schmargs::SchmargsError<Self::Item>> {
+            #[allow(non_snake_case)]
+            let #bindings = __schmargs_state;
+            Ok(#short)
+        }
+
+        fn parse_positional<__SchmargsIterator: ::core::iter::Iterator<Item = Self::Item>>(
+            __schmargs_state: &mut Self::State,
+            __schmargs_position: usize,
+            __schmargs_value: Self::Item,
+            args: &mut ::schmargs::utils::DumbIterator<Self::Item, __SchmargsIterator>,
+        ) -> ::core::result::Result<::core::option::Option<Self::Item>, ::schmargs::SchmargsError<Self::Item>> {
+            #[allow(non_snake_case)]
+            let #bindings = __schmargs_state;
+            Ok(#positional)
+        }
+
+        fn finish(__schmargs_state: Self::State) -> ::core::result::Result<Self, ::schmargs::SchmargsError<Self::Item>> {
+            #[allow(non_snake_case)]
+            let #bindings = __schmargs_state;
+            #checks
+            Ok(Self {
+                #finish
+                #finish_marker
+            })
+        }
+
+        #help
+    }
 }
 
 fn display_arg(arg: &Arg) -> String {
     if arg.kind() == ArgKind::Positional {
         let value_name = arg.value_name();
-        let mut value_name = if arg.is_option {
+        // Arguments that only fall back on an environment variable are still required
+        let mut value_name = if arg.is_option || arg.default_value().is_some() {
             format!("[{value_name}]")
         } else {
             value_name
@@ -587,18 +2325,14 @@ fn display_arg(arg: &Arg) -> String {
 
     if let Some(short) = arg.short() {
         string.push('-');
-        string.push_str(
-            snailquote::unescape(&short.to_string())
-                .expect("Failed to unescape string")
-                .as_str(),
-        );
+        string.push_str(&unescape(&short));
         if arg.long().is_some() {
             string.push_str(", ");
         }
     }
 
-    if let Some(long) = arg.long() {
-        string.push_str(long.to_string().as_str());
+    if let Some(long) = arg.display_long() {
+        string.push_str(long.as_str());
     }
 
     if arg.kind() == ArgKind::Option {
@@ -608,94 +2342,509 @@ fn display_arg(arg: &Arg) -> String {
         }
     }
 
+    // Flag can be repeated
+    if arg.kind() == ArgKind::Count {
+        string.push_str("...");
+    }
+
     string
 }
 
-fn impl_help_body(args: &[Arg]) -> TokenStream {
-    let pretty_args: Vec<_> = args.iter().map(|arg| (arg, display_arg(arg))).collect();
-    let mut body = {
-        let pretty_args = pretty_args.iter().map(|v| &v.1);
-        quote! {
+// Split `display_arg` at the start of the long flag's name, which is where the prefix of a
+// flattened field goes. Also returns the prefix expression to use
+fn display_arg_split(arg: &Arg) -> (String, TokenStream, String) {
+    let mut before = display_arg(arg);
+    match arg.display_long() {
+        Some(long) if arg.kind() != ArgKind::Positional => {
+            let index = before.find(&long).expect("Bug: expected long flag") + "--".len();
+            let after = before.split_off(index);
+            (before, quote! { __schmargs_prefix }, after)
+        }
+        _ => (
+            before,
+            quote! { ::schmargs::utils::FlagPrefix::NONE },
+            String::new(),
+        ),
+    }
+}
+
+// Methods of `Flatten` that write help entries
+fn impl_help_entries(
+    string_type: &TokenStream,
+    lifetime: &LifetimeParam,
+    args: &[Arg],
+    groups: &[GroupAttribute],
+) -> TokenStream {
+    let mut width_body: TokenStream = Default::default();
+    let mut arguments_body: TokenStream = Default::default();
+    let mut options_body: TokenStream = Default::default();
+    let mut group_bodies: Vec<TokenStream> = vec![Default::default(); groups.len()];
+    let mut flattened_groups: TokenStream = Default::default();
+
+    for arg in args {
+        if arg.kind() == ArgKind::Flatten {
+            let flatten = flatten_trait(lifetime, arg);
+            let prefix = arg.prefix();
+            width_body.extend(quote! {
+                min_indent = ::core::cmp::max(
+                    min_indent,
+                    #flatten::help_width(__schmargs_prefix.push(#prefix), hidden)
+                );
+            });
+            arguments_body.extend(quote! {
+                #flatten::write_arguments(&mut f, min_indent, long, hidden)?;
+            });
+            options_body.extend(quote! {
+                #flatten::write_options(&mut f, min_indent, __schmargs_prefix.push(#prefix), long, hidden)?;
+            });
+            flattened_groups.extend(quote! {
+                #flatten::write_groups(&mut f, min_indent, __schmargs_prefix.push(#prefix), long, hidden)?;
+            });
+            continue;
+        }
+
+        let (before, prefix, after) = display_arg_split(arg);
+        let mut suffix = String::new();
+        if let Some(env) = &arg.env {
+            suffix.push_str(&format!(" [env: {env}]"));
+        }
+        for (constraint, _) in arg.constraints() {
+            suffix.push_str(&format!(" [{constraint}]"));
+        }
+        let DocAttribute { short, long } = &arg.attr.doc;
+        let description = if short == long {
+            let description = format!("{short}{suffix}");
+            quote! { #description }
+        } else {
+            let short = format!("{short}{suffix}");
+            let long = format!("{long}{suffix}");
+            quote! { if long { #long } else { #short } }
+        };
+        let possible_values = impl_possible_values(string_type, arg);
+        // Hidden arguments are only shown with `show_hidden_with`
+        let shown = if arg.is_hidden() {
+            quote! { hidden }
+        } else {
+            quote! { true }
+        };
+        width_body.extend(quote! {
+            if #shown {
+                min_indent = ::core::cmp::max(
+                    min_indent,
+                    ::schmargs::utils::help_entry_width(#before, #prefix, #after) + 1
+                );
+            }
+        });
+        let visible_shorts = arg.short_aliases(true);
+        let visible_longs = arg.long_aliases(true);
+        let aliases = if visible_shorts.is_empty() && visible_longs.is_empty() {
+            quote! {}
+        } else {
+            quote! {
+                ::schmargs::utils::write_aliases(&mut f, __schmargs_prefix, &[#(#visible_shorts),*], &[#(#visible_longs),*])?;
+            }
+        };
+        let entry = quote! {
+            if #shown {
+                ::schmargs::utils::write_help_entry(&mut f, #before, #prefix, #after, min_indent, #description)?;
+                #aliases
+                #possible_values
+            }
+        };
+        if arg.kind() == ArgKind::Positional {
+            arguments_body.extend(entry);
+        } else if let Some(index) = groups.iter().position(|g| g.args.contains(&arg.ident)) {
+            group_bodies[index].extend(entry);
+        } else {
+            options_body.extend(entry);
+        }
+    }
+
+    let headings = groups
+        .iter()
+        .map(|group| format!("\n\n{}:", group.heading()));
+    let visible_groups = groups.iter().map(|group| !group.is_hidden(args));
+
+    quote! {
+        #[allow(unused_variables)]
+        fn help_width(__schmargs_prefix: ::schmargs::utils::FlagPrefix, hidden: bool) -> usize {
+            let mut min_indent = 0;
+            #width_body
+            min_indent
+        }
+
+        #[allow(unused_mut, unused_variables)]
+        fn write_arguments(
+            mut f: &mut ::schmargs::utils::HelpWriter<impl ::core::fmt::Write>,
+            min_indent: usize,
+            long: bool,
+            hidden: bool,
+        ) -> ::core::fmt::Result {
+            #arguments_body
+            Ok(())
+        }
+
+        #[allow(unused_mut, unused_variables)]
+        fn write_options(
+            mut f: &mut ::schmargs::utils::HelpWriter<impl ::core::fmt::Write>,
+            min_indent: usize,
+            __schmargs_prefix: ::schmargs::utils::FlagPrefix,
+            long: bool,
+            hidden: bool,
+        ) -> ::core::fmt::Result {
+            #options_body
+            Ok(())
+        }
+
+        #[allow(unused_mut, unused_variables)]
+        fn write_groups(
+            mut f: &mut ::schmargs::utils::HelpWriter<impl ::core::fmt::Write>,
+            min_indent: usize,
+            __schmargs_prefix: ::schmargs::utils::FlagPrefix,
+            long: bool,
+            hidden: bool,
+        ) -> ::core::fmt::Result {
             #(
-                min_indent = ::core::cmp::max(min_indent, str::len(#pretty_args) + 1);
+                if #visible_groups || hidden {
+                    write!(f, #headings)?;
+                    #group_bodies
+                }
             )*
+            #flattened_groups
+            Ok(())
         }
-    };
+    }
+}
 
-    body.extend(quote! {
-        writeln!(f, "{}", Self::DESCRIPTION)?;
-        writeln!(f)?;
-        write!(f, "Usage: {}", Self::USAGE)?;
-    });
+fn impl_help_body(lifetime: &LifetimeParam) -> TokenStream {
+    quote! {
+        min_indent = ::core::cmp::max(
+            min_indent,
+            <Self as ::schmargs::utils::Flatten<#lifetime>>::help_width(::schmargs::utils::FlagPrefix::NONE, __schmargs_hidden)
+        );
 
-    if pretty_args
-        .iter()
-        .any(|v| v.0.kind() == ArgKind::Positional)
-    {
-        body.extend(quote! {
+        ::schmargs::utils::write_description(&mut f, Self::DESCRIPTION, Self::LONG_DESCRIPTION, __schmargs_long)?;
+        ::schmargs::utils::write_usage::<Self>(&mut f, name)?;
+
+        let __schmargs_positionals = if __schmargs_hidden {
+            <Self as ::schmargs::utils::Flatten<#lifetime>>::POSITIONALS
+        } else {
+            <Self as ::schmargs::utils::Flatten<#lifetime>>::POSITIONALS
+                - <Self as ::schmargs::utils::Flatten<#lifetime>>::HIDDEN_POSITIONALS
+        };
+        if __schmargs_positionals != 0 {
             writeln!(f, "\n")?;
             write!(f, "Arguments:")?;
-        });
-        for arg in pretty_args
-            .iter()
-            .filter(|v| v.0.kind() == ArgKind::Positional)
-        {
-            let left_portion = &arg.1;
-            let right_portion = &arg.0.attr.doc.value;
-            body.extend(quote! {
-                write!(f, "\n{}", #left_portion)?;
-                for _ in 0..min_indent.saturating_sub(str::len(#left_portion)) {
-                    write!(f, " ")?;
-                }
-                write!(f, "{}", #right_portion)?;
-            });
+            <Self as ::schmargs::utils::Flatten<#lifetime>>::write_arguments(&mut f, min_indent, __schmargs_long, __schmargs_hidden)?;
         }
-    }
 
-    if pretty_args
-        .iter()
-        .any(|v| v.0.kind() == ArgKind::Flag || v.0.kind() == ArgKind::Option)
-    {
-        body.extend(quote! {
+        <Self as ::schmargs::utils::Flatten<#lifetime>>::write_groups(
+            &mut f,
+            min_indent,
+            ::schmargs::utils::FlagPrefix::NONE,
+            __schmargs_long,
+            __schmargs_hidden,
+        )?;
+
+        let __schmargs_options = <Self as ::schmargs::utils::Flatten<#lifetime>>::HELP_OPTIONS
+            || (__schmargs_hidden && <Self as ::schmargs::utils::Flatten<#lifetime>>::HIDDEN_OPTIONS);
+        if __schmargs_options {
             writeln!(f, "\n")?;
             write!(f, "Options:")?;
-        });
-        for arg in pretty_args
-            .iter()
-            .filter(|v| v.0.kind() == ArgKind::Flag || v.0.kind() == ArgKind::Option)
-        {
-            let left_portion = &arg.1;
-            let right_portion = &arg.0.attr.doc.value;
-            body.extend(quote! {
-                let mut revindent = str::len(#left_portion);
-                write!(f, "\n{}", #left_portion)?;
-            });
-
-            body.extend(quote! {
-                for _ in 0..min_indent.saturating_sub(revindent) {
-                    write!(f, " ")?;
-                }
-                write!(f, "{}", #right_portion)?;
-            });
+            <Self as ::schmargs::utils::Flatten<#lifetime>>::write_options(
+                &mut f,
+                min_indent,
+                ::schmargs::utils::FlagPrefix::NONE,
+                __schmargs_long,
+                __schmargs_hidden,
+            )?;
         }
+
+        Ok((min_indent, __schmargs_options))
     }
+}
 
-    body.extend(quote! {
-        Ok(min_indent)
+// Write the possible values of an argument to the help text, if any
+fn impl_possible_values(string_type: &TokenStream, arg: &Arg) -> TokenStream {
+    // The possible values of the type are replaced, or might not exist
+    let custom_values = matches!(
+        &arg.attr.arg,
+        Some(ArgAttribute {
+            possible_values: Some(_),
+            ..
+        }) | Some(ArgAttribute {
+            value_parser: Some(_),
+            ..
+        })
+    );
+    if custom_values || matches!(arg.kind(), ArgKind::Flag | ArgKind::Count) {
+        return quote! {};
+    }
+    let ty = &arg.ty;
+    quote! {
+        ::schmargs::utils::write_possible_values(
+            &mut f,
+            <#ty as ::schmargs::SchmargsField<#string_type>>::POSSIBLE_VALUES,
+        )?;
+    }
+}
+
+// Variant of `ValueHint` named by `#[arg(value_hint = ...)]`
+fn value_hint_variant(hint: &Ident) -> Option<&'static str> {
+    Some(match hint.to_string().as_str() {
+        "path" => "AnyPath",
+        "file" => "FilePath",
+        "dir" => "DirPath",
+        "hostname" => "Hostname",
+        "username" => "Username",
+        "command" => "CommandName",
+        _ => return None,
+    })
+}
+
+// Descriptions of arguments, as in `Schmargs::ARGS`
+fn impl_arg_specs(
+    string_type: &TokenStream,
+    lifetime: &LifetimeParam,
+    args: &[Arg],
+) -> TokenStream {
+    let specs = args.iter().map(|arg| {
+        let name = arg.ident.to_string();
+        let ty = &arg.ty;
+        let kind = match arg.kind() {
+            ArgKind::Flag => quote! { Flag },
+            ArgKind::Count => quote! { Count },
+            ArgKind::Option => quote! { Option },
+            ArgKind::Positional => quote! { Positional },
+            ArgKind::Flatten => {
+                let prefix = arg.prefix();
+                return quote! {
+                    ::schmargs::spec::ArgSpec {
+                        kind: ::schmargs::spec::ArgKind::Flatten {
+                            prefix: #prefix,
+                            args: <#ty as ::schmargs::Schmargs<#lifetime>>::ARGS,
+                        },
+                        name: #name,
+                        ..::schmargs::spec::ArgSpec::DEFAULT
+                    }
+                };
+            }
+        };
+        let takes_value = matches!(arg.kind(), ArgKind::Option | ArgKind::Positional);
+        let attr = arg.attr.arg.as_ref();
+        // The type might not implement `SchmargsField` if it has a `value_parser`
+        let field_type = attr.is_none_or(|attr| attr.value_parser.is_none()) && takes_value;
+
+        let short = match arg.short() {
+            Some(short) => quote! { ::core::option::Option::Some(#short) },
+            None => quote! { ::core::option::Option::None },
+        };
+        let long = match arg.long() {
+            Some(long) => {
+                let long = long.strip_prefix("--").expect("Bug: expected dashes");
+                quote! { ::core::option::Option::Some(#long) }
+            }
+            None => quote! { ::core::option::Option::None },
+        };
+        let visible_shorts = arg.short_aliases(true);
+        let visible_longs = arg.long_aliases(true);
+        let negated_long = match arg.negated_long() {
+            Some(long) => {
+                let long = long.strip_prefix("--").expect("Bug: expected dashes");
+                quote! { ::core::option::Option::Some(#long) }
+            }
+            None => quote! { ::core::option::Option::None },
+        };
+        let value_name = if takes_value {
+            arg.value_name()
+        } else {
+            String::new()
+        };
+        let value_hint = match attr.and_then(|attr| attr.value_hint.as_ref()) {
+            Some(hint) => {
+                let variant = Ident::new(
+                    value_hint_variant(hint).expect("Bug: expected valid value hint"),
+                    hint.span(),
+                );
+                quote! { ::schmargs::spec::ValueHint::#variant }
+            }
+            None if field_type => {
+                quote! { <#ty as ::schmargs::SchmargsField<#string_type>>::VALUE_HINT }
+            }
+            None => quote! { ::schmargs::spec::ValueHint::Unknown },
+        };
+        let possible_values = match attr.and_then(|attr| attr.possible_values.as_ref()) {
+            Some(values) => quote! { &[#(#values),*] },
+            None if field_type => {
+                quote! { <#ty as ::schmargs::SchmargsField<#string_type>>::POSSIBLE_VALUES }
+            }
+            None => quote! { &[] },
+        };
+        let default_value = match arg.default_value_text() {
+            Some(default_value) => quote! { ::core::option::Option::Some(#default_value) },
+            None => quote! { ::core::option::Option::None },
+        };
+        let env = match &arg.env {
+            Some(env) => quote! { ::core::option::Option::Some(#env) },
+            None => quote! { ::core::option::Option::None },
+        };
+        let DocAttribute {
+            short: description,
+            long: long_description,
+        } = &arg.attr.doc;
+        let optional = arg.is_optional();
+        let variadic = arg.is_collection;
+        let hidden = arg.is_hidden();
+
+        quote! {
+            ::schmargs::spec::ArgSpec {
+                kind: ::schmargs::spec::ArgKind::#kind,
+                name: #name,
+                short: #short,
+                long: #long,
+                visible_short_aliases: &[#(#visible_shorts),*],
+                visible_aliases: &[#(#visible_longs),*],
+                negated_long: #negated_long,
+                value_name: #value_name,
+                value_hint: #value_hint,
+                possible_values: #possible_values,
+                default_value: #default_value,
+                env: #env,
+                description: #description,
+                long_description: #long_description,
+                optional: #optional,
+                variadic: #variadic,
+                hidden: #hidden,
+            }
+        }
+    });
+
+    quote! { &[#(#specs),*] }
+}
+
+// Descriptions of subcommands, as in `Schmargs::SUBCOMMANDS`
+fn impl_command_specs(
+    lifetime: &LifetimeParam,
+    commands: &[Command],
+    types: &[TokenStream],
+) -> TokenStream {
+    let specs = commands.iter().zip(types).map(|(command, ty)| {
+        let name = command.name();
+        let DocAttribute {
+            short: description,
+            long: long_description,
+        } = &command.attr.doc;
+        let mut description = quote! { #description };
+        let mut long_description = quote! { #long_description };
+        // Fall back on the doc comment of the wrapped type
+        if matches!(command.kind, CommandKind::Newtype(_)) && command.attr.doc.long.is_empty() {
+            description = quote! { <#ty as ::schmargs::Schmargs<#lifetime>>::DESCRIPTION };
+            long_description =
+                quote! { <#ty as ::schmargs::Schmargs<#lifetime>>::LONG_DESCRIPTION };
+        }
+        quote! {
+            ::schmargs::spec::CommandSpec {
+                name: #name,
+                description: #description,
+                long_description: #long_description,
+                usage: ::schmargs::utils::usage_after_name(
+                    <#ty as ::schmargs::Schmargs<#lifetime>>::USAGE,
+                    <#ty as ::schmargs::Schmargs<#lifetime>>::NAME,
+                ),
+                args: <#ty as ::schmargs::Schmargs<#lifetime>>::ARGS,
+                subcommands: <#ty as ::schmargs::Schmargs<#lifetime>>::SUBCOMMANDS,
+            }
+        }
     });
-    body
+
+    quote! { &[#(#specs),*] }
 }
 
-fn impl_usage_body(command_name: &TokenStream, args: &[Arg]) -> TokenStream {
+// Positional arguments as shown in the usage text, including those of flattened fields
+fn impl_usage_positionals(lifetime: &LifetimeParam, args: &[Arg]) -> TokenStream {
+    let has_flattened = args.iter().any(|a| a.kind() == ArgKind::Flatten);
+    let pieces = args.iter().filter_map(|arg| match arg.kind() {
+        ArgKind::Positional if arg.is_hidden() => None,
+        ArgKind::Positional => {
+            let arg = format!(" {}", display_arg(arg));
+            Some(quote! { #arg })
+        }
+        ArgKind::Flatten => {
+            let flatten = flatten_trait(lifetime, arg);
+            Some(quote! { #flatten::USAGE_POSITIONALS })
+        }
+        ArgKind::Flag | ArgKind::Count | ArgKind::Option => None,
+    });
+
+    if has_flattened {
+        quote! {
+            ::schmargs::utils::ConstStr::<{ ::schmargs::utils::USAGE_CAPACITY }>::new()
+                #(.push(#pieces))*
+                .as_str()
+        }
+    } else {
+        quote! {
+            concat!("" #(, #pieces)*)
+        }
+    }
+}
+
+fn impl_usage_body(
+    command_name: &TokenStream,
+    lifetime: &LifetimeParam,
+    args: &[Arg],
+    groups: &[GroupAttribute],
+) -> TokenStream {
+    // Flags in groups are shown with their group
+    let usage_options = args
+        .iter()
+        .any(|v| v.is_usage_option() && v.group(groups).is_none());
+    let usage_groups: String = groups.iter().map(|group| group.usage(args)).collect();
+
+    if args.iter().any(|a| a.kind() == ArgKind::Flatten) {
+        let flattened = args
+            .iter()
+            .filter(|a| a.kind() == ArgKind::Flatten)
+            .map(|a| flatten_trait(lifetime, a));
+        return quote! {
+            ::schmargs::utils::ConstStr::<{ ::schmargs::utils::USAGE_CAPACITY }>::new()
+                .push(#command_name)
+                .push_required("", &<Self as ::schmargs::utils::Flatten<#lifetime>>::REQUIRED)
+                // `|` rather than `||`, which would prevent promoting the temporary
+                .push_if(#usage_options #(| #flattened::USAGE_OPTIONS)*, " [OPTIONS]")
+                .push(#usage_groups)
+                .push(<Self as ::schmargs::utils::Flatten<#lifetime>>::USAGE_POSITIONALS)
+                .as_str()
+        };
+    }
+
     let mut body = quote! {};
 
-    if args.iter().any(|v| v.kind() == ArgKind::Flag) {
+    for arg in args
+        .iter()
+        .filter(|a| a.is_required_option() && a.group(groups).is_none() && !a.is_hidden())
+    {
+        let (dashes, rest) = arg.required_usage();
+        let usage = format!(" {dashes}{rest}");
+        body.extend(quote! {
+            , #usage
+        });
+    }
+    if usage_options {
         body.extend(quote! {
             , " [OPTIONS]"
         });
     }
+    if !usage_groups.is_empty() {
+        body.extend(quote! {
+            , #usage_groups
+        });
+    }
 
-    for arg in args.iter().filter(|v| v.kind() == ArgKind::Positional) {
+    for arg in args
+        .iter()
+        .filter(|v| v.kind() == ArgKind::Positional && !v.is_hidden())
+    {
         let arg = display_arg(arg);
         body.extend(quote! {
             , " ", #arg
@@ -706,3 +2855,126 @@ fn impl_usage_body(command_name: &TokenStream, args: &[Arg]) -> TokenStream {
         concat!(#command_name #body)
     }
 }
+
+fn impl_command_parse_body(
+    lifetime: &LifetimeParam,
+    commands: &[Command],
+    types: &[TokenStream],
+) -> TokenStream {
+    let mut match_body: TokenStream = Default::default();
+
+    for (command, ty) in commands.iter().zip(types) {
+        let name = command.name();
+        let ident = &command.ident;
+        let body = match &command.kind {
+            CommandKind::Struct(args) => {
+                let members = args.iter().map(|arg| &arg.member);
+                quote! {
+                    let __schmargs_variant = <#ty as ::schmargs::Schmargs<#lifetime>>::parse(args)?;
+                    Ok(Self::#ident {
+                        #(#members: __schmargs_variant.#members,)*
+                    })
+                }
+            }
+            CommandKind::Newtype(_) => quote! {
+                Ok(Self::#ident(<#ty as ::schmargs::Schmargs<#lifetime>>::parse(args)?))
+            },
+        };
+        match_body.extend(quote! {
+            #name => { #body },
+        });
+    }
+
+    quote! {
+        let mut args = args;
+        let __schmargs_command = match args.next() {
+            Some(command) => command,
+            None => {
+                return ::core::result::Result::Err(::schmargs::SchmargsError::ExpectedValue("COMMAND"));
+            }
+        };
+        match ::core::convert::AsRef::<str>::as_ref(&__schmargs_command) {
+            #match_body
+            __schmargs_misc_command => {
+                if __schmargs_misc_command.len() > 2 && __schmargs_misc_command.starts_with("--") {
+                    ::core::result::Result::Err(::schmargs::SchmargsError::NoSuchLongFlag {
+                        got: __schmargs_command,
+                        suggestion: None,
+                    })
+                } else if let Some(short) = __schmargs_misc_command.strip_prefix('-').and_then(|v| v.chars().next()) {
+                    ::core::result::Result::Err(::schmargs::SchmargsError::NoSuchShortFlag(short))
+                } else {
+                    ::core::result::Result::Err(::schmargs::SchmargsError::NoSuchSubcommand {
+                        suggestion: ::schmargs::utils::suggest_command(
+                            __schmargs_misc_command,
+                            <Self as ::schmargs::Schmargs<#lifetime>>::SUBCOMMANDS,
+                        ),
+                        got: __schmargs_command,
+                    })
+                }
+            }
+        }
+    }
+}
+
+// Commands are listed with the descriptions in `SUBCOMMANDS`, which fall back on the doc comment
+// of a wrapped type
+fn impl_command_help_body(lifetime: &LifetimeParam) -> TokenStream {
+    quote! {
+        let __schmargs_commands = <Self as ::schmargs::Schmargs<#lifetime>>::SUBCOMMANDS;
+        for command in __schmargs_commands {
+            min_indent = ::core::cmp::max(min_indent, command.name.len() + 1);
+        }
+        ::schmargs::utils::write_description(&mut f, Self::DESCRIPTION, Self::LONG_DESCRIPTION, __schmargs_long)?;
+        ::schmargs::utils::write_usage::<Self>(&mut f, name)?;
+        writeln!(f, "\n")?;
+        write!(f, "Commands:")?;
+        for command in __schmargs_commands {
+            f.wrap(None)?;
+            write!(f, "\n{}", command.name)?;
+            for _ in 0..min_indent.saturating_sub(command.name.len()) {
+                write!(f, " ")?;
+            }
+            f.wrap(Some(min_indent))?;
+            write!(f, "{}", command.description)?;
+        }
+        Ok((min_indent, false))
+    }
+}
+
+// Body of `write_subcommand_help`, which passes the rest of the path on to the subcommand it
+// starts with, along with the name of the command so far
+fn impl_command_subcommand_help(
+    lifetime: &LifetimeParam,
+    commands: &[Command],
+    types: &[TokenStream],
+) -> TokenStream {
+    let indices = (0..types.len()).map(Index::from);
+    let names = commands.iter().map(Command::name);
+    let help_body = impl_command_help_body(lifetime);
+
+    quote! {
+        match path.split_first() {
+            #(
+                Some((&#indices, rest)) => {
+                    let name = ::schmargs::utils::CommandName { parent: name, name: #names };
+                    <#types as ::schmargs::Schmargs<#lifetime>>::write_subcommand_help(rest, &name, f, min_indent, width, kind)
+                }
+            )*
+            _ => {
+                let mut f = ::schmargs::utils::HelpWriter::new(f, width);
+                let __schmargs_long = kind == ::schmargs::utils::HelpKind::Long;
+                #help_body
+            }
+        }
+    }
+}
+
+fn impl_command_usage_body(command_name: &TokenStream, commands: &[Command]) -> TokenStream {
+    let names: Vec<_> = commands.iter().map(|command| command.name()).collect();
+    let names = format!(" <{}>", names.join("|"));
+
+    quote! {
+        concat!(#command_name, #names)
+    }
+}
@@ -1,5 +1,6 @@
 mod derive_impl;
 mod utils;
+mod value_derive_impl;
 
 use proc_macro::TokenStream;
 use syn::{parse_macro_input, DeriveInput};
@@ -7,5 +8,12 @@ use syn::{parse_macro_input, DeriveInput};
 #[proc_macro_derive(Schmargs, attributes(arg, schmargs))]
 pub fn schmargs_derive(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
-    derive_impl::schmargs_derive_impl(input).unwrap()
+    derive_impl::schmargs_derive_impl(input).unwrap_or_else(|err| err.into_compile_error().into())
+}
+
+#[proc_macro_derive(SchmargsValue, attributes(value))]
+pub fn schmargs_value_derive(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    value_derive_impl::schmargs_value_derive_impl(input)
+        .unwrap_or_else(|err| err.into_compile_error().into())
 }
@@ -1,30 +1,178 @@
-use proc_macro2::{Ident, Literal, TokenStream, TokenTree};
+use proc_macro2::{Delimiter, Ident, Literal, TokenStream, TokenTree};
 use quote::quote;
-use syn::Generics;
+use syn::{Error, Generics, Lit, Result};
+
+// Return early with an error pointing at `$tokens`
+macro_rules! bail {
+    ($tokens:expr, $($arg:tt)*) => {
+        return Err(syn::Error::new_spanned($tokens, format!($($arg)*)))
+    };
+}
+pub(crate) use bail;
+
+// Errors that are reported together, so the user doesn't have to fix them one at a time
+#[derive(Default)]
+pub(crate) struct Errors(Option<Error>);
+
+impl Errors {
+    pub(crate) fn push(&mut self, error: Error) {
+        match &mut self.0 {
+            Some(errors) => errors.combine(error),
+            None => self.0 = Some(error),
+        }
+    }
+
+    // Return the value, or save the error for later
+    pub(crate) fn check<T>(&mut self, result: Result<T>) -> Option<T> {
+        result.map_err(|error| self.push(error)).ok()
+    }
+
+    pub(crate) fn finish(self) -> Result<()> {
+        self.0.map_or(Ok(()), Err)
+    }
+}
 
 pub(crate) trait TokenTreeExt {
-    fn unwrap_as_literal(self) -> Literal;
-    fn unwrap_as_ident(self) -> Ident;
+    fn into_str_literal(self) -> Result<Literal>;
+    fn into_char_literal(self) -> Result<Literal>;
+    fn into_int_literal(self) -> Result<Literal>;
+    fn into_ident(self) -> Result<Ident>;
+    fn into_str_literal_list(self) -> Result<Vec<Literal>>;
+    fn into_ident_list(self) -> Result<Vec<Ident>>;
+}
+
+// Unwrap a literal of the kind accepted by `is_kind`
+fn into_literal(token: TokenTree, expected: &str, is_kind: fn(&Lit) -> bool) -> Result<Literal> {
+    match token {
+        TokenTree::Literal(literal) if is_kind(&Lit::new(literal.clone())) => Ok(literal),
+        token => bail!(token, "Expected {expected}"),
+    }
+}
+
+// Unwrap a list, e.g. `[a, b]`
+fn into_list<T>(token: TokenTree, item: fn(TokenTree) -> Result<T>) -> Result<Vec<T>> {
+    match token {
+        TokenTree::Group(group) if group.delimiter() == Delimiter::Bracket => group
+            .stream()
+            .into_iter()
+            .filter(|v| !matches!(v, TokenTree::Punct(punct) if punct.as_char() == ','))
+            .map(item)
+            .collect(),
+        token => bail!(token, "Expected list"),
+    }
 }
 
 impl TokenTreeExt for TokenTree {
-    fn unwrap_as_literal(self) -> Literal {
+    fn into_str_literal(self) -> Result<Literal> {
+        into_literal(self, "string literal", |lit| matches!(lit, Lit::Str(_)))
+    }
+
+    fn into_char_literal(self) -> Result<Literal> {
+        into_literal(self, "character literal", |lit| matches!(lit, Lit::Char(_)))
+    }
+
+    fn into_int_literal(self) -> Result<Literal> {
+        into_literal(self, "integer literal", |lit| matches!(lit, Lit::Int(_)))
+    }
+
+    fn into_ident(self) -> Result<Ident> {
         match self {
-            Self::Literal(val) => val,
-            _ => {
-                panic!("Expected literal, but unwrapped something else");
-            }
+            Self::Ident(val) => Ok(val),
+            token => bail!(token, "Expected identifier"),
         }
     }
 
-    fn unwrap_as_ident(self) -> Ident {
+    // Unwrap a list of string literals, e.g. `["a", "b"]`
+    fn into_str_literal_list(self) -> Result<Vec<Literal>> {
+        into_list(self, TokenTreeExt::into_str_literal)
+    }
+
+    // Unwrap an ident or a list of idents, e.g. `a` or `[a, b]`
+    fn into_ident_list(self) -> Result<Vec<Ident>> {
         match self {
-            Self::Ident(val) => val,
-            _ => {
-                panic!("Expected ident, but unwrapped something else");
+            Self::Ident(ident) => Ok(vec![ident]),
+            token => into_list(token, TokenTreeExt::into_ident),
+        }
+    }
+}
+
+// Value of a string or character literal, which has already been checked by `TokenTreeExt`
+pub(crate) fn unescape(literal: &Literal) -> String {
+    match Lit::new(literal.clone()) {
+        Lit::Str(lit) => lit.value(),
+        Lit::Char(lit) => lit.value().into(),
+        _ => unreachable!("Bug: expected string or character literal"),
+    }
+}
+
+// Key-value pairs from an attribute, e.g. `#[arg(short, long = "foo")]`
+// Keys may be repeated, e.g. `#[value(alias = "a", alias = "b")]`
+#[derive(Default)]
+pub(crate) struct AttributeMap(Vec<(Ident, Option<TokenTree>)>);
+
+impl AttributeMap {
+    pub(crate) fn insert(&mut self, key: Ident, value: Option<TokenTree>) {
+        self.0.push((key, value));
+    }
+
+    // Remove key, returning all of its keys and values
+    fn take(&mut self, key: &str) -> Vec<(Ident, Option<TokenTree>)> {
+        let (taken, kept) = std::mem::take(&mut self.0)
+            .into_iter()
+            .partition(|(k, _)| k == key);
+        self.0 = kept;
+        taken
+    }
+
+    // Remove key, returning its last value
+    pub(crate) fn remove(&mut self, key: &str) -> Option<Option<TokenTree>> {
+        self.take(key).pop().map(|(_, value)| value)
+    }
+
+    // Remove key that can be bare or set to a bool, e.g. `hide` or `hide = false`, returning its
+    // last value
+    pub(crate) fn remove_bool(&mut self, key: &str) -> Result<Option<bool>> {
+        let Some(value) = self.remove(key) else {
+            return Ok(None);
+        };
+        let Some(value) = value else {
+            return Ok(Some(true));
+        };
+        match &value {
+            TokenTree::Ident(ident) if ident == "true" || ident == "false" => {
+                Ok(Some(ident == "true"))
             }
+            _ => bail!(value, "Expected `true` or `false`"),
         }
     }
+
+    // Remove key that requires a value, returning its last value
+    pub(crate) fn remove_value(&mut self, key: &str) -> Result<Option<TokenTree>> {
+        Ok(self.remove_all_values(key)?.pop())
+    }
+
+    // Remove key that requires a value, returning all of its values
+    pub(crate) fn remove_all_values(&mut self, key: &str) -> Result<Vec<TokenTree>> {
+        self.take(key)
+            .into_iter()
+            .map(|(key, value)| match value {
+                Some(value) => Ok(value),
+                None => bail!(&key, "`{key}` expects a value"),
+            })
+            .collect()
+    }
+
+    // Fail if there are any keys that weren't removed
+    pub(crate) fn finish(self) -> Result<()> {
+        let mut errors = Errors::default();
+        for (key, _) in self.0 {
+            errors.push(Error::new_spanned(
+                &key,
+                format!("Unknown argument `{key}`"),
+            ));
+        }
+        errors.finish()
+    }
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
@@ -60,3 +208,21 @@ pub(crate) fn copy_generics(generics: &Generics, bounds: CopyGenericsBoundOption
     }
     gen
 }
+
+// Convert an UpperCamelCase identifier to kebab-case, e.g. `AddFile` to "add-file"
+pub(crate) fn to_kebab_case(ident: &str) -> String {
+    let mut string = String::new();
+    for (i, c) in ident.chars().enumerate() {
+        if c.is_uppercase() {
+            if i != 0 {
+                string.push('-');
+            }
+            string.extend(c.to_lowercase());
+        } else if c == '_' {
+            string.push('-');
+        } else {
+            string.push(c);
+        }
+    }
+    string
+}
@@ -0,0 +1,99 @@
+use crate::derive_impl::{parse_attribute, SchmargsAttribute, ValueAttribute};
+use crate::utils::{bail, unescape, Errors};
+use quote::quote;
+use syn::{Data, DeriveInput, Fields, Result};
+
+struct Value {
+    ident: syn::Ident,
+    name: String,
+    aliases: Vec<String>,
+}
+
+pub fn schmargs_value_derive_impl(input: DeriveInput) -> Result<proc_macro::TokenStream> {
+    let enum_name = input.ident;
+
+    let Data::Enum(data) = &input.data else {
+        bail!(enum_name, "expected an enum");
+    };
+
+    if input.generics.lt_token.is_some() {
+        bail!(
+            input.generics,
+            "`SchmargsValue` can not be derived on an enum with generics"
+        );
+    }
+
+    let mut errors = Errors::default();
+    let mut values = Vec::new();
+    for variant in &data.variants {
+        if let Some(value) = errors.check(parse_variant(variant)) {
+            values.push(value);
+        }
+    }
+    errors.finish()?;
+
+    let names = values.iter().map(|v| &v.name);
+    let match_body = values.iter().map(|v| {
+        let ident = &v.ident;
+        let name = &v.name;
+        let aliases = &v.aliases;
+        quote! {
+            #name #(| #aliases)* => ::core::result::Result::Ok(Self::#ident),
+        }
+    });
+
+    Ok(quote! {
+        impl<__SchmargsItem: ::core::convert::AsRef<str>> ::schmargs::SchmargsField<__SchmargsItem> for #enum_name {
+            const POSSIBLE_VALUES: &'static [&'static str] = &[#(#names),*];
+
+            fn parse_str(val: __SchmargsItem) -> ::core::result::Result<Self, ::schmargs::SchmargsError<__SchmargsItem>> {
+                match ::core::convert::AsRef::<str>::as_ref(&val) {
+                    #(#match_body)*
+                    __schmargs_value => {
+                        let possible_values = <Self as ::schmargs::SchmargsField<__SchmargsItem>>::POSSIBLE_VALUES;
+                        ::core::result::Result::Err(::schmargs::SchmargsError::InvalidValue {
+                            suggestion: ::schmargs::utils::suggest_value(__schmargs_value, possible_values),
+                            got: val,
+                            possible_values,
+                        })
+                    }
+                }
+            }
+        }
+    }
+    .into())
+}
+
+fn parse_variant(variant: &syn::Variant) -> Result<Value> {
+    if !matches!(variant.fields, Fields::Unit) {
+        bail!(
+            variant,
+            "expected variant `{}` to be a unit variant",
+            variant.ident
+        );
+    }
+
+    let mut attribute = None;
+    for attr in variant.attrs.iter().filter(|a| a.path().is_ident("value")) {
+        if attribute.is_some() {
+            bail!(attr, "Was not expecting two `value` attributes");
+        }
+        let SchmargsAttribute::Value(attr) = parse_attribute(attr)? else {
+            unreachable!("Bug: expected value attribute");
+        };
+        attribute = Some(attr);
+    }
+    let ValueAttribute { name, aliases } = attribute.unwrap_or(ValueAttribute {
+        name: None,
+        aliases: Vec::new(),
+    });
+
+    Ok(Value {
+        name: name
+            .as_ref()
+            .map(unescape)
+            .unwrap_or_else(|| crate::utils::to_kebab_case(&variant.ident.to_string())),
+        aliases: aliases.iter().map(unescape).collect(),
+        ident: variant.ident.clone(),
+    })
+}
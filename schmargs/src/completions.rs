@@ -0,0 +1,540 @@
+//! Shell completion scripts
+//!
+//! Scripts complete short and long flags, the values of options, and subcommands. Values are
+//! completed from their possible values, or from their [ValueHint], e.g. paths for
+//! [PathBuf](std::path::PathBuf) fields and `#[arg(value_hint = dir)]`.
+//!
+//! # Example
+//!
+//! A hidden flag that prints a completion script
+//!
+//! ```
+//! use schmargs::{completions::{self, Shell}, ArgsWithHelp, Schmargs};
+//!
+//! /// Copy a file
+//! #[derive(Schmargs)]
+//! #[schmargs(name = "copy")]
+//! struct BareArgs<'a> {
+//!     /// Print a completion script
+//!     #[arg(long, hide)]
+//!     generate_completions: Option<Shell>,
+//!     /// Source file
+//!     source: Option<&'a std::path::Path>,
+//! }
+//! type Args<'a> = ArgsWithHelp<BareArgs<'a>>;
+//!
+//! let args = Args::parse("--generate-completions bash".split_whitespace()).unwrap();
+//! if let Args::Args(BareArgs {
+//!     generate_completions: Some(shell),
+//!     ..
+//! }) = args
+//! {
+//!     completions::generate::<Args>(shell, std::io::stdout()).unwrap();
+//! }
+//! ```
+use crate::{
+    spec::{flatten_args, ArgKind, ArgSpec, CommandSpec, ValueHint},
+    utils::suggest_value,
+    Schmargs, SchmargsError, SchmargsField,
+};
+use std::{fmt::Write as _, io};
+
+/// A shell that completion scripts can be generated for
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Shell {
+    /// The Bourne Again SHell
+    Bash,
+    /// The Z shell
+    Zsh,
+    /// The friendly interactive shell
+    Fish,
+}
+
+impl<T: AsRef<str>> SchmargsField<T> for Shell {
+    const POSSIBLE_VALUES: &'static [&'static str] = &["bash", "zsh", "fish"];
+
+    fn parse_str(val: T) -> Result<Self, SchmargsError<T>> {
+        match val.as_ref() {
+            "bash" => Ok(Self::Bash),
+            "zsh" => Ok(Self::Zsh),
+            "fish" => Ok(Self::Fish),
+            _ => {
+                let possible_values = <Self as SchmargsField<T>>::POSSIBLE_VALUES;
+                Err(SchmargsError::InvalidValue {
+                    suggestion: suggest_value(val.as_ref(), possible_values),
+                    got: val,
+                    possible_values,
+                })
+            }
+        }
+    }
+}
+
+/// Write a completion script for `S` to `out`. Arguments with `#[arg(hide)]` aren't completed
+///
+/// # Example
+///
+/// ```
+/// use schmargs::{completions::{self, Shell}, Schmargs};
+///
+/// /// Say hello
+/// #[derive(Schmargs)]
+/// #[schmargs(name = "hello")]
+/// struct Args {
+///     /// Say it loudly
+///     #[arg(short, long)]
+///     loud: bool,
+/// }
+///
+/// let mut script = Vec::new();
+/// completions::generate::<Args>(Shell::Fish, &mut script).unwrap();
+/// assert_eq!(
+///     String::from_utf8(script).unwrap(),
+///     "complete -c hello -s l -l loud -d 'Say it loudly'\n"
+/// );
+/// ```
+pub fn generate<'a, S: Schmargs<'a>>(shell: Shell, mut out: impl io::Write) -> io::Result<()> {
+    let command = CommandSpec::of::<S>();
+    let mut script = String::new();
+    match shell {
+        Shell::Bash => bash(&mut script, &command),
+        Shell::Zsh => zsh(&mut script, &command),
+        Shell::Fish => fish(&mut script, &command),
+    }
+    .expect("Bug: writing to a String can't fail");
+    out.write_all(script.as_bytes())
+}
+
+// A command and the names of the commands it's nested in, starting with the outermost
+struct Node<'c> {
+    path: Vec<&'c str>,
+    command: &'c CommandSpec,
+}
+
+impl<'c> Node<'c> {
+    fn child(&self, command: &'c CommandSpec) -> Self {
+        let mut path = self.path.clone();
+        path.push(command.name);
+        Self { path, command }
+    }
+
+    // Identifier for the command, e.g. "git__remote__add"
+    fn ident(&self) -> String {
+        let path: Vec<String> = self
+            .path
+            .iter()
+            .map(|name| name.replace(|c: char| !c.is_ascii_alphanumeric(), "_"))
+            .collect();
+        path.join("__")
+    }
+}
+
+// All commands, with each command before its subcommands
+fn walk(command: &CommandSpec) -> Vec<Node<'_>> {
+    fn visit<'c>(node: Node<'c>, nodes: &mut Vec<Node<'c>>) {
+        let children: Vec<_> = node
+            .command
+            .subcommands
+            .iter()
+            .map(|c| node.child(c))
+            .collect();
+        nodes.push(node);
+        for child in children {
+            visit(child, nodes);
+        }
+    }
+
+    let mut nodes = Vec::new();
+    let root = Node {
+        path: vec![command.name],
+        command,
+    };
+    visit(root, &mut nodes);
+    nodes
+}
+
+// Arguments that are completed, along with their prefix
+fn visible_args(command: &CommandSpec) -> Vec<(String, &'static ArgSpec)> {
+    flatten_args(command.args)
+        .into_iter()
+        .filter(|(_, arg)| !arg.hidden)
+        .collect()
+}
+
+// Short flags of an argument, e.g. `["-o"]`
+fn shorts(arg: &ArgSpec) -> Vec<String> {
+    arg.short
+        .iter()
+        .chain(arg.visible_short_aliases)
+        .map(|short| format!("-{short}"))
+        .collect()
+}
+
+// Long flags of an argument, e.g. `["--db-host"]`
+fn longs(prefix: &str, arg: &ArgSpec) -> Vec<String> {
+    let mut longs: Vec<String> = arg
+        .long
+        .iter()
+        .chain(arg.visible_aliases)
+        .map(|long| format!("--{prefix}{long}"))
+        .collect();
+    if let Some(long) = arg.negated_long {
+        longs.push(format!("--{prefix}{long}"));
+    }
+    longs
+}
+
+fn bash(f: &mut String, root: &CommandSpec) -> std::fmt::Result {
+    let nodes = walk(root);
+    let function = format!("_{}", nodes[0].ident());
+
+    writeln!(f, "{function}() {{")?;
+    writeln!(f, "    local i cur prev cmd opts")?;
+    writeln!(f, "    COMPREPLY=()")?;
+    writeln!(f, "    cur=\"${{COMP_WORDS[COMP_CWORD]}}\"")?;
+    writeln!(f, "    prev=\"${{COMP_WORDS[COMP_CWORD-1]}}\"")?;
+    writeln!(f, "    cmd=\"{}\"", nodes[0].ident())?;
+    writeln!(f)?;
+
+    // Find the subcommand being completed
+    if nodes.len() > 1 {
+        writeln!(f, "    for ((i = 1; i < COMP_CWORD; i++)); do")?;
+        writeln!(f, "        case \"${{cmd}},${{COMP_WORDS[i]}}\" in")?;
+        for node in &nodes {
+            for subcommand in node.command.subcommands {
+                writeln!(f, "            \"{},{}\")", node.ident(), subcommand.name)?;
+                writeln!(
+                    f,
+                    "                cmd=\"{}\"",
+                    node.child(subcommand).ident()
+                )?;
+                writeln!(f, "                ;;")?;
+            }
+        }
+        writeln!(f, "        esac")?;
+        writeln!(f, "    done")?;
+        writeln!(f)?;
+    }
+
+    writeln!(f, "    case \"${{cmd}}\" in")?;
+    for node in &nodes {
+        let args = visible_args(node.command);
+        let mut words: Vec<String> = Vec::new();
+        for (prefix, arg) in &args {
+            match arg.kind {
+                ArgKind::Positional => {
+                    words.extend(arg.possible_values.iter().map(|v| v.to_string()))
+                }
+                _ => {
+                    words.extend(shorts(arg));
+                    words.extend(longs(prefix, arg));
+                }
+            }
+        }
+        words.extend(node.command.subcommands.iter().map(|c| c.name.to_string()));
+
+        writeln!(f, "        \"{}\")", node.ident())?;
+        writeln!(f, "            opts=\"{}\"", words.join(" "))?;
+
+        // Complete the value of the option before the cursor
+        let options: Vec<_> = args
+            .iter()
+            .filter(|(_, arg)| arg.kind == ArgKind::Option)
+            .collect();
+        if !options.is_empty() {
+            writeln!(f, "            case \"${{prev}}\" in")?;
+            for (prefix, arg) in options {
+                let mut flags = shorts(arg);
+                flags.extend(longs(prefix, arg));
+                writeln!(f, "                {})", flags.join("|"))?;
+                writeln!(f, "                    COMPREPLY=({})", bash_values(arg))?;
+                writeln!(f, "                    return 0")?;
+                writeln!(f, "                    ;;")?;
+            }
+            writeln!(f, "            esac")?;
+        }
+
+        writeln!(
+            f,
+            "            COMPREPLY=($(compgen -W \"${{opts}}\" -- \"${{cur}}\"))"
+        )?;
+        // Positional arguments that aren't completed from a list of values
+        let mut hints: Vec<String> = args
+            .iter()
+            .filter(|(_, arg)| arg.kind == ArgKind::Positional && arg.possible_values.is_empty())
+            .map(|(_, arg)| bash_values(arg))
+            .filter(|values| !values.is_empty())
+            .collect();
+        hints.dedup();
+        for hint in hints {
+            writeln!(f, "            COMPREPLY+=({hint})")?;
+        }
+        writeln!(f, "            return 0")?;
+        writeln!(f, "            ;;")?;
+    }
+    writeln!(f, "    esac")?;
+    writeln!(f, "}}")?;
+    writeln!(f)?;
+    writeln!(
+        f,
+        "complete -F {function} -o bashdefault -o default {}",
+        root.name
+    )
+}
+
+// Array of completions of the value of an argument. Empty if nothing is known about the value,
+// which falls back to the default completion
+fn bash_values(arg: &ArgSpec) -> String {
+    if !arg.possible_values.is_empty() {
+        return format!(
+            "$(compgen -W \"{}\" -- \"${{cur}}\")",
+            arg.possible_values.join(" ")
+        );
+    }
+    let option = match arg.value_hint {
+        ValueHint::Unknown => return String::new(),
+        ValueHint::AnyPath | ValueHint::FilePath => "-f",
+        ValueHint::DirPath => "-d",
+        ValueHint::Hostname => "-A hostname",
+        ValueHint::Username => "-u",
+        ValueHint::CommandName => "-c",
+    };
+    format!("$(compgen {option} -- \"${{cur}}\")")
+}
+
+fn zsh(f: &mut String, root: &CommandSpec) -> std::fmt::Result {
+    let nodes = walk(root);
+    let function = format!("_{}", nodes[0].ident());
+
+    writeln!(f, "#compdef {}", root.name)?;
+    for node in &nodes {
+        writeln!(f)?;
+        writeln!(f, "_{}() {{", node.ident())?;
+        writeln!(f, "    local curcontext=\"$curcontext\" state line")?;
+        writeln!(f, "    _arguments -s -S -C \\")?;
+        for (prefix, arg) in visible_args(node.command) {
+            for spec in zsh_specs(&prefix, arg) {
+                writeln!(f, "        '{spec}' \\")?;
+            }
+        }
+        if node.command.subcommands.is_empty() {
+            writeln!(f, "        && return 0")?;
+            writeln!(f, "}}")?;
+            continue;
+        }
+
+        writeln!(f, "        ': :->command' \\")?;
+        writeln!(f, "        '*:: :->args' \\")?;
+        writeln!(f, "        && return 0")?;
+        writeln!(f)?;
+        writeln!(f, "    case $state in")?;
+        writeln!(f, "        command)")?;
+        writeln!(f, "            local -a commands")?;
+        writeln!(f, "            commands=(")?;
+        for subcommand in node.command.subcommands {
+            let name = zsh_escape(&subcommand.name.replace(':', "\\:"));
+            if subcommand.description.is_empty() {
+                writeln!(f, "                '{name}'")?;
+            } else {
+                let description = zsh_escape(subcommand.description);
+                writeln!(f, "                '{name}:{description}'")?;
+            }
+        }
+        writeln!(f, "            )")?;
+        writeln!(f, "            _describe -t commands 'command' commands")?;
+        writeln!(f, "            ;;")?;
+        writeln!(f, "        args)")?;
+        writeln!(f, "            case $line[1] in")?;
+        for subcommand in node.command.subcommands {
+            writeln!(
+                f,
+                "                '{}') _{} ;;",
+                zsh_escape(subcommand.name),
+                node.child(subcommand).ident()
+            )?;
+        }
+        writeln!(f, "            esac")?;
+        writeln!(f, "            ;;")?;
+        writeln!(f, "    esac")?;
+        writeln!(f, "}}")?;
+    }
+    writeln!(f)?;
+    writeln!(f, "if [ \"$funcstack[1]\" = \"{function}\" ]; then")?;
+    writeln!(f, "    {function} \"$@\"")?;
+    writeln!(f, "else")?;
+    writeln!(f, "    compdef {function} {}", root.name)?;
+    writeln!(f, "fi")
+}
+
+// Specs of an argument for `_arguments`, e.g. `(-o --output)--output=[Output file]:OUTPUT:_files`
+fn zsh_specs(prefix: &str, arg: &ArgSpec) -> Vec<String> {
+    let description = zsh_escape(arg.description)
+        .replace('[', "\\[")
+        .replace(']', "\\]");
+    let action = zsh_action(arg);
+    match arg.kind {
+        ArgKind::Positional => {
+            let colons = if arg.variadic {
+                "*:"
+            } else if arg.optional {
+                "::"
+            } else {
+                ":"
+            };
+            let value_name = zsh_escape(arg.value_name).replace(':', "\\:");
+            vec![format!("{colons}{value_name}:{action}")]
+        }
+        ArgKind::Flatten { .. } => unreachable!("Bug: expected flattened arguments"),
+        ArgKind::Flag | ArgKind::Count | ArgKind::Option => {
+            let shorts = shorts(arg);
+            let longs = longs(prefix, arg);
+            // Flags that can be repeated don't exclude themselves
+            let exclusions = if arg.kind == ArgKind::Count || arg.variadic {
+                String::from("*")
+            } else {
+                let flags: Vec<String> = shorts.iter().chain(&longs).cloned().collect();
+                format!("({})", flags.join(" "))
+            };
+            let value = if arg.kind == ArgKind::Option {
+                format!(
+                    ":{}:{action}",
+                    zsh_escape(arg.value_name).replace(':', "\\:")
+                )
+            } else {
+                String::new()
+            };
+            let shorts = shorts.into_iter().map(|short| {
+                let separator = if arg.kind == ArgKind::Option { "+" } else { "" };
+                format!("{exclusions}{short}{separator}[{description}]{value}")
+            });
+            let longs = longs.into_iter().map(|long| {
+                let separator = if arg.kind == ArgKind::Option { "=" } else { "" };
+                format!("{exclusions}{long}{separator}[{description}]{value}")
+            });
+            shorts.chain(longs).collect()
+        }
+    }
+}
+
+// Completion of the value of an argument
+fn zsh_action(arg: &ArgSpec) -> String {
+    if !arg.possible_values.is_empty() {
+        let values: Vec<String> = arg
+            .possible_values
+            .iter()
+            .map(|value| zsh_escape(value).replace(' ', "\\ "))
+            .collect();
+        return format!("({})", values.join(" "));
+    }
+    String::from(match arg.value_hint {
+        ValueHint::Unknown => "_default",
+        ValueHint::AnyPath | ValueHint::FilePath => "_files",
+        ValueHint::DirPath => "_files -/",
+        ValueHint::Hostname => "_hosts",
+        ValueHint::Username => "_users",
+        ValueHint::CommandName => "_command_names -e",
+    })
+}
+
+// Escape for use in single quotes
+fn zsh_escape(string: &str) -> String {
+    string.replace('\'', "'\\''")
+}
+
+fn fish(f: &mut String, root: &CommandSpec) -> std::fmt::Result {
+    for node in walk(root) {
+        // Arguments of a command are completed until one of its subcommands is passed
+        let mut conditions: Vec<String> = node.path[1..]
+            .iter()
+            .map(|name| format!("__fish_seen_subcommand_from {name}"))
+            .collect();
+        let subcommands: Vec<&str> = node.command.subcommands.iter().map(|c| c.name).collect();
+        if !subcommands.is_empty() {
+            if node.path.len() == 1 {
+                conditions.push(String::from("__fish_use_subcommand"));
+            } else {
+                conditions.push(format!(
+                    "not __fish_seen_subcommand_from {}",
+                    subcommands.join(" ")
+                ));
+            }
+        }
+        let condition = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!(" -n \"{}\"", conditions.join("; and "))
+        };
+
+        for (prefix, arg) in visible_args(node.command) {
+            let mut line = format!("complete -c {}{condition}", root.name);
+            match arg.kind {
+                ArgKind::Positional => {
+                    let values = fish_values(arg);
+                    // Files are completed by default
+                    if values.is_empty() || values == "-F" {
+                        continue;
+                    }
+                    write!(line, " {values}")?;
+                }
+                _ => {
+                    for short in arg.short.iter().chain(arg.visible_short_aliases) {
+                        write!(line, " -s {short}")?;
+                    }
+                    for long in arg.long.iter().chain(arg.visible_aliases) {
+                        write!(line, " -l {prefix}{long}")?;
+                    }
+                    if let Some(long) = arg.negated_long {
+                        write!(line, " -l {prefix}{long}")?;
+                    }
+                    if arg.kind == ArgKind::Option {
+                        line.push_str(" -r");
+                        let values = fish_values(arg);
+                        if !values.is_empty() {
+                            write!(line, " {values}")?;
+                        }
+                    }
+                }
+            }
+            if !arg.description.is_empty() {
+                write!(line, " -d '{}'", fish_escape(arg.description))?;
+            }
+            writeln!(f, "{line}")?;
+        }
+
+        for subcommand in node.command.subcommands {
+            write!(
+                f,
+                "complete -c {}{condition} -f -a '{}'",
+                root.name,
+                fish_escape(subcommand.name)
+            )?;
+            if !subcommand.description.is_empty() {
+                write!(f, " -d '{}'", fish_escape(subcommand.description))?;
+            }
+            writeln!(f)?;
+        }
+    }
+    Ok(())
+}
+
+// Options of `complete` that complete the value of an argument. Empty if nothing is known about
+// the value, which falls back to completing files
+fn fish_values(arg: &ArgSpec) -> String {
+    if !arg.possible_values.is_empty() {
+        let values: Vec<String> = arg.possible_values.iter().map(|v| fish_escape(v)).collect();
+        return format!("-f -a '{}'", values.join(" "));
+    }
+    String::from(match arg.value_hint {
+        ValueHint::Unknown => "",
+        ValueHint::AnyPath | ValueHint::FilePath => "-F",
+        ValueHint::DirPath => "-f -a '(__fish_complete_directories)'",
+        ValueHint::Hostname => "-f -a '(__fish_print_hostnames)'",
+        ValueHint::Username => "-f -a '(__fish_complete_users)'",
+        ValueHint::CommandName => "-f -a '(__fish_complete_command)'",
+    })
+}
+
+// Escape for use in single quotes
+fn fish_escape(string: &str) -> String {
+    string.replace('\\', "\\\\").replace('\'', "\\'")
+}
@@ -4,6 +4,11 @@ use core::{
 };
 use derive_more::{Display, From};
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+
 /// The error type used in this crate
 #[derive(Clone, Debug, From, PartialEq, Eq)]
 pub enum SchmargsError<T> {
@@ -13,11 +18,58 @@ pub enum SchmargsError<T> {
     /// Passed a short flag that doesn't exist
     NoSuchShortFlag(char),
     /// Passed a long flag that doesn't exist
-    NoSuchLongFlag(T),
+    NoSuchLongFlag {
+        /// The flag that was passed, e.g. `--verbos`
+        got: T,
+        /// Closest long flag, without the leading "--", if any is close enough
+        suggestion: Option<&'static str>,
+    },
+    /// Passed a subcommand that doesn't exist
+    NoSuchSubcommand {
+        /// The subcommand that was passed
+        got: T,
+        /// Closest subcommand, if any is close enough
+        suggestion: Option<&'static str>,
+    },
     /// Did not expect this value
     UnexpectedValue(T),
-    /// Expected a value to an argument
+    /// Expected a value to an argument. Contains its flag (e.g. `--name`) or, for positional
+    /// arguments, its value name (e.g. `FILE`)
     ExpectedValue(&'static str),
+    /// Passed a value that isn't one of the possible values
+    InvalidValue {
+        /// The value that was passed
+        got: T,
+        /// Values that would have been accepted
+        possible_values: &'static [&'static str],
+        /// Closest possible value, if any is close enough
+        suggestion: Option<&'static str>,
+    },
+    /// Passed a value to a flag that doesn't take one, e.g. `--verbose=1`
+    UnexpectedFlagValue(T),
+    /// The value of an argument violates a constraint, e.g. `range = 1..=65535`. Contains the
+    /// argument, as its flag (e.g. `--port`) or, for positional arguments, its value name (e.g.
+    /// `PORT`), and the constraint
+    ConstraintViolation(&'static str, &'static str),
+    /// The `value_parser` of an argument failed. Contains the name of the argument and the error
+    /// returned by the parser
+    ValueParserFailed(&'static str, ValueParserError),
+    /// Passed an argument without an argument it requires. Contains both arguments, as their flag
+    /// (e.g. `--output`) or, for positional arguments, their value name (e.g. `FILE`)
+    Requires(&'static str, &'static str),
+    /// Passed two arguments that conflict. Contains both arguments, like [SchmargsError::Requires]
+    ConflictsWith(&'static str, &'static str),
+    /// Didn't pass an argument, or any of the arguments that make it optional. Contains the
+    /// argument and the others, like [SchmargsError::Requires]
+    RequiredUnlessPresent(&'static str, &'static [&'static str]),
+    /// Didn't pass any argument of a required group. Contains the name of the group and the flags
+    /// of its arguments
+    MissingGroup(&'static str, &'static [&'static str]),
+    /// Passed more than one argument of a group with `multiple = false`. Contains the name of the
+    /// group and the flags of two of the arguments that were passed
+    GroupConflict(&'static str, &'static str, &'static str),
+    /// Didn't pass a required option. Contains its flag, e.g. `--name`
+    MissingRequiredArgument(&'static str),
 }
 
 /// A type-stripped version of [SchmargsError], built from [SchmargsError::strip]
@@ -30,14 +82,182 @@ pub enum StrippedSchmargsError {
     #[display("Found invalid option: '-{_0}'")]
     NoSuchShortFlag(char),
     /// See [SchmargsError::NoSuchLongFlag]
-    #[display("Found invalid option")]
-    NoSuchLongFlag,
+    #[display("Found invalid option{}", Suggestion("--", *suggestion))]
+    NoSuchLongFlag {
+        /// See [SchmargsError::NoSuchLongFlag::suggestion]
+        suggestion: Option<&'static str>,
+    },
+    /// See [SchmargsError::NoSuchSubcommand]
+    #[display("Found invalid subcommand{}", Suggestion("", *suggestion))]
+    NoSuchSubcommand {
+        /// See [SchmargsError::NoSuchSubcommand::suggestion]
+        suggestion: Option<&'static str>,
+    },
     /// See [SchmargsError::UnexpectedValue]
     #[display("Unexpected positional value")]
     UnexpectedValue,
     /// See [SchmargsError::ExpectedValue]
     #[display("Expected value for '{_0}'")]
     ExpectedValue(&'static str),
+    /// See [SchmargsError::InvalidValue]
+    #[display(
+        "Invalid value (possible values: {}){}",
+        ValueList(possible_values),
+        Suggestion("", *suggestion)
+    )]
+    InvalidValue {
+        /// See [SchmargsError::InvalidValue::possible_values]
+        possible_values: &'static [&'static str],
+        /// See [SchmargsError::InvalidValue::suggestion]
+        suggestion: Option<&'static str>,
+    },
+    /// See [SchmargsError::UnexpectedFlagValue]
+    #[display("Option does not take a value")]
+    UnexpectedFlagValue,
+    /// See [SchmargsError::ConstraintViolation]
+    #[display("Invalid value for '{_0}' ({_1})")]
+    ConstraintViolation(&'static str, &'static str),
+    /// See [SchmargsError::ValueParserFailed]
+    #[display("Invalid value for '{_0}': {_1}")]
+    ValueParserFailed(&'static str, ValueParserError),
+    /// See [SchmargsError::Requires]
+    #[display("'{_0}' requires '{_1}'")]
+    Requires(&'static str, &'static str),
+    /// See [SchmargsError::ConflictsWith]
+    #[display("'{_0}' conflicts with '{_1}'")]
+    ConflictsWith(&'static str, &'static str),
+    /// See [SchmargsError::RequiredUnlessPresent]
+    #[display("Expected '{_0}' or one of: {}", ValueList(_1))]
+    RequiredUnlessPresent(&'static str, &'static [&'static str]),
+    /// See [SchmargsError::MissingGroup]
+    #[display("Expected one of the '{_0}' arguments: {}", ValueList(_1))]
+    MissingGroup(&'static str, &'static [&'static str]),
+    /// See [SchmargsError::GroupConflict]
+    #[display("Only one of the '{_0}' arguments can be passed, got '{_1}' and '{_2}'")]
+    GroupConflict(&'static str, &'static str, &'static str),
+    /// See [SchmargsError::MissingRequiredArgument]
+    #[display("Missing required option '{_0}'")]
+    MissingRequiredArgument(&'static str),
+}
+
+/// Error returned by the `value_parser` of an argument, as formatted by its [Display]
+/// implementation. Without the `alloc` feature, the message is truncated to
+/// [ValueParserError::CAPACITY] bytes
+#[derive(Clone)]
+pub struct ValueParserError {
+    #[cfg(feature = "alloc")]
+    message: String,
+    #[cfg(not(feature = "alloc"))]
+    buffer: [u8; Self::CAPACITY],
+    #[cfg(not(feature = "alloc"))]
+    len: usize,
+}
+
+impl ValueParserError {
+    /// Maximum length of the message without the `alloc` feature, in bytes
+    pub const CAPACITY: usize = 64;
+
+    /// Format `err` into a new error
+    pub fn new(err: impl Display) -> Self {
+        #[cfg(feature = "alloc")]
+        let this = Self {
+            message: alloc::string::ToString::to_string(&err),
+        };
+        #[cfg(not(feature = "alloc"))]
+        let this = {
+            use fmt::Write;
+
+            let mut this = Self {
+                buffer: [0; Self::CAPACITY],
+                len: 0,
+            };
+            // Writing only fails once the message is truncated
+            let _ = write!(TruncatingWriter(&mut this), "{err}");
+            this
+        };
+        this
+    }
+
+    /// The error message
+    pub fn as_str(&self) -> &str {
+        #[cfg(feature = "alloc")]
+        let message = &self.message;
+        #[cfg(not(feature = "alloc"))]
+        let message = core::str::from_utf8(&self.buffer[..self.len])
+            .expect("Bug: message is truncated at a character boundary");
+        message
+    }
+}
+
+impl Display for ValueParserError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Debug for ValueParserError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_tuple("ValueParserError")
+            .field(&self.as_str())
+            .finish()
+    }
+}
+
+impl PartialEq for ValueParserError {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for ValueParserError {}
+
+// Writes to the buffer of a `ValueParserError` until it's full
+#[cfg(not(feature = "alloc"))]
+struct TruncatingWriter<'a>(&'a mut ValueParserError);
+
+#[cfg(not(feature = "alloc"))]
+impl fmt::Write for TruncatingWriter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let error = &mut *self.0;
+        let mut len = s.len().min(ValueParserError::CAPACITY - error.len);
+        while !s.is_char_boundary(len) {
+            len -= 1;
+        }
+        error.buffer[error.len..error.len + len].copy_from_slice(&s.as_bytes()[..len]);
+        error.len += len;
+        if len == s.len() {
+            Ok(())
+        } else {
+            Err(fmt::Error)
+        }
+    }
+}
+
+// Comma-separated list of values
+pub(crate) struct ValueList(pub &'static [&'static str]);
+
+impl Display for ValueList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        for (i, value) in self.0.iter().enumerate() {
+            if i != 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{value}")?;
+        }
+        Ok(())
+    }
+}
+
+// ", did you mean '<prefix><suggestion>'?", if there is a suggestion
+struct Suggestion(&'static str, Option<&'static str>);
+
+impl Display for Suggestion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match self.1 {
+            Some(suggestion) => write!(f, ", did you mean '{}{suggestion}'?", self.0),
+            None => Ok(()),
+        }
+    }
 }
 
 impl<T> SchmargsError<T> {
@@ -48,8 +268,44 @@ impl<T> SchmargsError<T> {
             SchmargsError::ParseInt(val) => StrippedSchmargsError::ParseInt(val),
             SchmargsError::NoSuchShortFlag(val) => StrippedSchmargsError::NoSuchShortFlag(val),
             SchmargsError::ExpectedValue(val) => StrippedSchmargsError::ExpectedValue(val),
-            SchmargsError::NoSuchLongFlag(_) => StrippedSchmargsError::NoSuchLongFlag,
+            SchmargsError::NoSuchLongFlag { suggestion, .. } => {
+                StrippedSchmargsError::NoSuchLongFlag { suggestion }
+            }
+            SchmargsError::NoSuchSubcommand { suggestion, .. } => {
+                StrippedSchmargsError::NoSuchSubcommand { suggestion }
+            }
             SchmargsError::UnexpectedValue(_) => StrippedSchmargsError::UnexpectedValue,
+            SchmargsError::InvalidValue {
+                possible_values,
+                suggestion,
+                ..
+            } => StrippedSchmargsError::InvalidValue {
+                possible_values,
+                suggestion,
+            },
+            SchmargsError::UnexpectedFlagValue(_) => StrippedSchmargsError::UnexpectedFlagValue,
+            SchmargsError::ConstraintViolation(arg, constraint) => {
+                StrippedSchmargsError::ConstraintViolation(arg, constraint)
+            }
+            SchmargsError::ValueParserFailed(arg, err) => {
+                StrippedSchmargsError::ValueParserFailed(arg, err)
+            }
+            SchmargsError::Requires(arg, other) => StrippedSchmargsError::Requires(arg, other),
+            SchmargsError::ConflictsWith(arg, other) => {
+                StrippedSchmargsError::ConflictsWith(arg, other)
+            }
+            SchmargsError::RequiredUnlessPresent(arg, others) => {
+                StrippedSchmargsError::RequiredUnlessPresent(arg, others)
+            }
+            SchmargsError::MissingGroup(group, args) => {
+                StrippedSchmargsError::MissingGroup(group, args)
+            }
+            SchmargsError::GroupConflict(group, arg, other) => {
+                StrippedSchmargsError::GroupConflict(group, arg, other)
+            }
+            SchmargsError::MissingRequiredArgument(flag) => {
+                StrippedSchmargsError::MissingRequiredArgument(flag)
+            }
         }
     }
 }
@@ -67,8 +323,21 @@ impl<T: fmt::Display> Display for SchmargsError<T> {
             Self::NoSuchShortFlag(val) => {
                 write!(f, "{}", StrippedSchmargsError::NoSuchShortFlag(*val))
             }
-            Self::NoSuchLongFlag(val) => {
-                write!(f, "{}: '{val}'", StrippedSchmargsError::NoSuchLongFlag)
+            Self::NoSuchLongFlag { got, suggestion } => {
+                write!(
+                    f,
+                    "{}: '{got}'{}",
+                    StrippedSchmargsError::NoSuchLongFlag { suggestion: None },
+                    Suggestion("--", *suggestion)
+                )
+            }
+            Self::NoSuchSubcommand { got, suggestion } => {
+                write!(
+                    f,
+                    "{}: '{got}'{}",
+                    StrippedSchmargsError::NoSuchSubcommand { suggestion: None },
+                    Suggestion("", *suggestion)
+                )
             }
             Self::UnexpectedValue(val) => {
                 write!(f, "{}: '{val}'", StrippedSchmargsError::UnexpectedValue)
@@ -76,6 +345,62 @@ impl<T: fmt::Display> Display for SchmargsError<T> {
             Self::ExpectedValue(val) => {
                 write!(f, "{}", StrippedSchmargsError::ExpectedValue(val))
             }
+            Self::UnexpectedFlagValue(val) => {
+                write!(f, "{}: '{val}'", StrippedSchmargsError::UnexpectedFlagValue)
+            }
+            Self::ConstraintViolation(arg, constraint) => {
+                write!(
+                    f,
+                    "{}",
+                    StrippedSchmargsError::ConstraintViolation(arg, constraint)
+                )
+            }
+            Self::ValueParserFailed(arg, err) => {
+                // Same as the stripped error, which would need a copy of `err`
+                write!(f, "Invalid value for '{arg}': {err}")
+            }
+            Self::Requires(arg, other) => {
+                write!(f, "{}", StrippedSchmargsError::Requires(arg, other))
+            }
+            Self::ConflictsWith(arg, other) => {
+                write!(f, "{}", StrippedSchmargsError::ConflictsWith(arg, other))
+            }
+            Self::RequiredUnlessPresent(arg, others) => {
+                write!(
+                    f,
+                    "{}",
+                    StrippedSchmargsError::RequiredUnlessPresent(arg, others)
+                )
+            }
+            Self::MissingGroup(group, args) => {
+                write!(f, "{}", StrippedSchmargsError::MissingGroup(group, args))
+            }
+            Self::GroupConflict(group, arg, other) => {
+                write!(
+                    f,
+                    "{}",
+                    StrippedSchmargsError::GroupConflict(group, arg, other)
+                )
+            }
+            Self::MissingRequiredArgument(flag) => {
+                write!(
+                    f,
+                    "{}",
+                    StrippedSchmargsError::MissingRequiredArgument(flag)
+                )
+            }
+            Self::InvalidValue {
+                got,
+                possible_values,
+                suggestion,
+            } => {
+                write!(
+                    f,
+                    "Invalid value '{got}' (possible values: {}){}",
+                    ValueList(possible_values),
+                    Suggestion("", *suggestion)
+                )
+            }
         }
     }
 }
@@ -1,4 +1,7 @@
 use crate::error::SchmargsError;
+use crate::spec::ValueHint;
+#[cfg(feature = "alloc")]
+use crate::utils::StringLike;
 #[cfg(feature = "alloc")]
 extern crate alloc;
 #[cfg(feature = "alloc")]
@@ -8,6 +11,14 @@ use alloc::vec::Vec;
 
 /// A field that can be parsed by Schmargs
 pub trait SchmargsField<T>: Sized {
+    /// Values this type can be parsed from, if there's a limited set of them. These are listed in
+    /// the help text
+    const POSSIBLE_VALUES: &'static [&'static str] = &[];
+
+    /// What kind of value this is, which shells use to complete it. This is set as the
+    /// [value_hint](crate::spec::ArgSpec::value_hint) of arguments of this type
+    const VALUE_HINT: ValueHint = ValueHint::Unknown;
+
     /// Construct type from string
     fn parse_str(val: T) -> Result<Self, SchmargsError<T>>;
     /// Construct type from iterator
@@ -69,6 +80,8 @@ impl<'a> SchmargsField<&'a str> for &'a str {
 
 #[cfg(feature = "std")]
 impl<'a> SchmargsField<&'a str> for &'a std::path::Path {
+    const VALUE_HINT: ValueHint = ValueHint::AnyPath;
+
     fn parse_str(val: &'a str) -> Result<Self, SchmargsError<&'a str>> {
         Ok(val.as_ref())
     }
@@ -76,6 +89,8 @@ impl<'a> SchmargsField<&'a str> for &'a std::path::Path {
 
 #[cfg(feature = "std")]
 impl<T: Into<std::path::PathBuf>> SchmargsField<T> for std::path::PathBuf {
+    const VALUE_HINT: ValueHint = ValueHint::AnyPath;
+
     fn parse_str(val: T) -> Result<Self, SchmargsError<T>> {
         Ok(val.into())
     }
@@ -95,28 +110,11 @@ impl<'a> SchmargsField<&'a str> for String {
     }
 }
 
-trait StringLike: Sized + AsRef<str> {
-    fn split_commas(self) -> impl Iterator<Item = Self>;
-}
-
-impl<'a> StringLike for &'a str {
-    fn split_commas(self) -> impl Iterator<Item = Self> {
-        self.split(',')
-    }
-}
-
-#[cfg(feature = "alloc")]
-impl StringLike for String {
-    fn split_commas(self) -> impl Iterator<Item = Self> {
-        let val: &str = self.as_ref();
-        let val = val.split(',').map(Self::from);
-        let val: Vec<String> = val.collect();
-        val.into_iter()
-    }
-}
-
 #[cfg(feature = "alloc")]
 impl<T: StringLike, Item: SchmargsField<T>> SchmargsField<T> for Vec<Item> {
+    const POSSIBLE_VALUES: &'static [&'static str] = Item::POSSIBLE_VALUES;
+    const VALUE_HINT: ValueHint = Item::VALUE_HINT;
+
     fn parse_str(val: T) -> Result<Self, SchmargsError<T>> {
         let mut vec = Vec::with_capacity(1);
         for val in val.split_commas() {
@@ -136,6 +134,9 @@ impl<T: StringLike, Item: SchmargsField<T>> SchmargsField<T> for Vec<Item> {
 }
 
 impl<U, T: SchmargsField<U>> SchmargsField<U> for Option<T> {
+    const POSSIBLE_VALUES: &'static [&'static str] = T::POSSIBLE_VALUES;
+    const VALUE_HINT: ValueHint = T::VALUE_HINT;
+
     fn parse_str(val: U) -> Result<Self, SchmargsError<U>> {
         Ok(Some(T::parse_str(val)?))
     }
@@ -9,6 +9,32 @@
 //! * Optional arguments
 //! * Multi-arg positional arguments and options with [std::vec::Vec]
 //! * Custom and default short and long flags
+//! * Counting flags, e.g. `-vvv`
+//! * Environment variable fallbacks
+//! * Flag aliases
+//! * Value constraints, e.g. `range = 1..=65535`
+//! * Argument groups, e.g. exactly one of `--tcp`, `--udp`, or `--unix`
+//! * Help text from doc comments, with the first paragraph as the short help and the whole comment
+//!   as the long help (`Schmargs::long_help`)
+//! * Help text wrapped to a given width ([HelpObject::with_width]), or with the `std` feature to
+//!   the terminal width set by the `COLUMNS` environment variable
+//! * GNU-style `--option=value` syntax
+//! * Attached short option values (`-n5`, `-xvfarchive.tar`)
+//! * Subcommands, by deriving on an enum
+//! * Positional-only tuple structs, e.g. `struct Reboot(u32);`, and unit structs that take no
+//!   arguments. Fields of tuple structs are named after their index, e.g. `ARG0`, unless they have
+//!   a `value_name`
+//! * Shared arguments, with `#[arg(flatten)]`
+//! * Enum values, with `SchmargsValue`
+//! * "Did you mean" suggestions for mistyped long flags, subcommands, and enum values, without
+//!   `alloc`
+//! * Shell completion scripts for bash, zsh, and fish ([completions]). Requires the `std` feature
+//! * roff man pages ([man]), which can be generated from a build script. Requires the `std`
+//!   feature
+//! * Markdown reference pages ([markdown]), which can be committed and diffed. Requires the `std`
+//!   feature
+//! * Static argument metadata ([Schmargs::ARGS]) for building tools on top of, which is
+//!   `#![no_std]`-friendly
 //! * A [wrapper](ArgsWithHelp) that allows for `--help` functionality
 //!
 //! # Todo
@@ -29,6 +55,19 @@
 //!   associated type of the [core::iter::Iterator] type passed to [Schmargs::parse]. This defaults
 //!   to `&str` with an appropriate lifetime. If you're in an `std` environment and plan on parsing
 //!   arguments passed to your program with `Schmargs::parse_env`, `iterates_over` MUST be specified.
+//! * `env_prefix=<str literal>` - Prefix of the environment variables of arguments with `env`
+//!   but no variable name, e.g. `env_prefix = "APP_"` makes `port` fall back on `APP_PORT`.
+//! * `group(name=<str literal>, args=[<argument>, ...], [required], [multiple=<bool>])` - Group
+//!   flags and options under their own heading in the help text. With `required`, at least one
+//!   argument of the group must be passed, and with `multiple = false`, at most one. Missing groups
+//!   are rejected with `SchmargsError::MissingGroup`, and extra ones with
+//!   `SchmargsError::GroupConflict`. Can be repeated.
+//! * `show_hidden_with=<str literal>` - A long flag, e.g. `"--help-all"`, that makes
+//!   [ArgsWithHelp] return a help request whose help text includes hidden arguments, see
+//!   [HelpRequest::shows_hidden].
+//!
+//! When deriving on an enum, `#[schmargs(name=<str literal>)]` can also be specified on a variant
+//! to set the name of its subcommand. Defaults to the variant name in kebab-case.
 //!
 //! ## `args`
 //!
@@ -44,6 +83,77 @@
 //!   help and usage text.
 //! * `default_value[=<expression>]` - Set the default value of the argument. Defaults to
 //!   [Default::default]
+//! * `flatten` - Parse the flags, options, and positional arguments of this field as if they
+//!   belonged to the parent. The type of the field must also derive [Schmargs]. Conflicting flags
+//!   are rejected at compile time.
+//! * `prefix=<str literal>` - Prefix the long flags of a `flatten` field, e.g. `prefix = "db-"`
+//!   turns `--host` into `--db-host`.
+//! * `action=count` - Count the occurrences of a flag instead of taking a value, e.g. `-vvv`.
+//!   The field must be an unsigned integer such as `u8` or `usize`.
+//! * `negatable` - Also accept `--no-<long>`, which sets a `bool` flag to false. The last
+//!   occurrence wins. Useful with `default_value = true`.
+//! * `env[=<str literal>]` - Fall back on an environment variable if the option or positional
+//!   argument isn't passed on the command line. This takes precedence over `default_value`. If no
+//!   value is provided, it will default to the uppercase argument name. Requires the `std` feature.
+//!   When iterating over `&str`, the values of environment variables are leaked.
+//! * `alias=<str literal>` - An additional long flag that isn't shown in the help text. Can be
+//!   repeated.
+//! * `visible_alias=<str literal>` - Like `alias`, but shown in the help text.
+//! * `short_alias=<char literal>` - An additional short flag that isn't shown in the help text.
+//!   Can be repeated.
+//! * `visible_short_alias=<char literal>` - Like `short_alias`, but shown in the help text.
+//! * `hide` - Leave the argument out of the help and usage text. It can still be passed, and is
+//!   shown by `Schmargs::help_all`.
+//! * `range=<range expression>` - Require the value of an option or positional argument to be
+//!   in a range, e.g. `range = 1..=65535`. Applies to each value of an `Option` or collection.
+//! * `possible_values=[<str literal>, ...]` - Require the value of a string argument to be one of
+//!   the listed values. Applies to each value of an `Option` or collection.
+//! * `min_len=<integer literal>` - Require a collection to have at least this many values.
+//! * `max_len=<integer literal>` - Require a collection to have at most this many values.
+//! * `value_parser=<path>` - Parse values with this function instead of `SchmargsField::parse_str`.
+//!   The function takes the `iterates_over` type and returns `Result<T, E>`, where `T` is the type
+//!   of the argument, or the type of each value of an `Option` or collection, and `E` implements
+//!   `Display`, e.g. `u16::from_str`. Errors are returned as `SchmargsError::ValueParserFailed`, with
+//!   the message truncated to `ValueParserError::CAPACITY` bytes without `alloc`.
+//! * `requires=<argument>` - Reject this argument unless another argument is also passed, e.g.
+//!   `requires = format` or `requires = [format, output]`.
+//! * `conflicts_with=<argument>` - Reject this argument if another argument is also passed.
+//! * `required_unless_present=<argument>` - Require this argument unless another argument is
+//!   passed. With a list of arguments, any one of them makes this argument optional. The field
+//!   must be an `Option` or have a `default_value`, which is used when it's left out.
+//! * `value_hint=<path|file|dir|hostname|username|command>` - What kind of value an option or
+//!   positional argument takes, which completion scripts use to complete it. Paths are detected
+//!   from `PathBuf` and `&Path` fields.
+//!
+//! Arguments named by `requires`, `conflicts_with`, and `required_unless_present` must be fields of
+//! the same struct. An option or positional argument counts as passed if it's on the command line,
+//! regardless of `env` and `default_value`, and a flag counts as passed if it's set.
+//!
+//! Constraints are listed in the help text. Values that violate them are rejected with
+//! `SchmargsError::ConstraintViolation`.
+//!
+//! Options that aren't an `Option` and have no `default_value` are required. They're listed in
+//! the usage text, e.g. `prog --name <NAME> [OPTIONS] FILE`, and missing ones are rejected with
+//! `SchmargsError::MissingRequiredArgument`.
+//!
+//! Keys that don't take a value, like `hide` and `flatten`, can also be set to a bool, e.g.
+//! `hide = false`.
+//!
+//! Two arguments can't share a flag, and a required positional argument can't follow an optional
+//! or variadic one. Both are compile errors. A flag that shadows `-h/--help` or `-v/--version` of a
+//! wrapper fails to compile where the type is wrapped. Subcommands may use those flags.
+//!
+//! ## `value`
+//!
+//! This is an optional attribute that should be specified on a variant of an enum that derives
+//! `SchmargsValue`.
+//!
+//! Arguments:
+//!
+//! * `name=<str literal>` - The value that selects this variant. Defaults to the variant name in
+//!   kebab-case.
+//! * `alias=<str literal>` - An additional value that selects this variant. This can be specified
+//!   more than once. Aliases are not shown in the help text.
 //!
 //! # Example
 //!
@@ -125,16 +235,133 @@
 //! assert_eq!(args.kick_shins, false);
 //! assert_eq!(args.person, "Dagan");
 //! ```
+//!
+//! # Subcommands
+//!
+//! Deriving on an enum creates a command whose variants are subcommands. The first positional
+//! argument selects the variant. A variant either has named fields, which are parsed like the
+//! fields of a struct, or wraps a single type that implements [Schmargs], which allows
+//! subcommands to nest. With [ArgsWithHelp], `--help` after a subcommand shows the help text of
+//! that subcommand.
+//!
+//! ```
+//! use schmargs::{ArgsWithHelp, Schmargs};
+//!
+//! /// Manage remote repositories
+//! #[derive(Schmargs)]
+//! enum RemoteArgs<'a> {
+//!     /// Add a remote
+//!     Add {
+//!         /// Name of the remote
+//!         name: &'a str,
+//!     },
+//!     /// Remove a remote
+//!     #[schmargs(name = "rm")]
+//!     Remove {
+//!         /// Name of the remote
+//!         name: &'a str,
+//!     },
+//! }
+//!
+//! /// A stupid content tracker
+//! #[derive(Schmargs)]
+//! #[schmargs(name = "git")]
+//! enum Args<'a> {
+//!     /// Record changes to the repository
+//!     Commit {
+//!         /// Commit all changed files
+//!         #[arg(short, long)]
+//!         all: bool,
+//!     },
+//!     /// Manage remote repositories
+//!     Remote(RemoteArgs<'a>),
+//! }
+//!
+//! assert_eq!(Args::USAGE, "git <commit|remote>");
+//!
+//! let args = Args::parse("remote rm origin".split_whitespace()).unwrap();
+//! assert!(matches!(args, Args::Remote(RemoteArgs::Remove { name: "origin" })));
+//!
+//! // Nested subcommands are shown after the commands they're reached through
+//! let args = ArgsWithHelp::<Args>::parse("remote rm --help".split_whitespace()).unwrap();
+//! assert!(args.to_string().contains("Usage: git remote rm NAME"));
+//! ```
+//!
+//! # Enum Values
+//!
+//! Deriving `SchmargsValue` on an enum with unit variants allows it to be used as the type of an
+//! argument. The possible values are listed in the help text.
+//!
+//! ```
+//! use schmargs::{Schmargs, SchmargsValue};
+//!
+//! #[derive(SchmargsValue, Debug, PartialEq)]
+//! enum Format {
+//!     Json,
+//!     #[value(alias = "yml")]
+//!     Yaml,
+//!     #[value(name = "txt")]
+//!     Text,
+//! }
+//!
+//! /// A program that prints stuff
+//! #[derive(Schmargs)]
+//! struct Args {
+//!     /// Output format
+//!     #[arg(short, long)]
+//!     format: Format,
+//! }
+//!
+//! let args = Args::parse("--format yml".split_whitespace()).unwrap();
+//! assert_eq!(args.format, Format::Yaml);
+//!
+//! assert!(Args::parse("--format xml".split_whitespace()).is_err());
+//! ```
+//!
+//! # Argument Metadata
+//!
+//! The derive macro describes each argument in [Schmargs::ARGS] and each subcommand in
+//! [Schmargs::SUBCOMMANDS]. These are plain `'static` data, so they work without `alloc` and can
+//! be placed in flash. Tools like [completions] are built on them.
+//!
+//! ```
+//! use schmargs::{spec::ArgKind, Schmargs};
+//!
+//! /// Blink an LED
+//! #[derive(Schmargs)]
+//! struct Args {
+//!     /// Delay between blinks in milliseconds
+//!     #[arg(short, long, default_value = 500)]
+//!     delay: u32,
+//!     /// Number of LEDs to blink
+//!     leds: Option<u8>,
+//! }
+//!
+//! let [delay, leds] = Args::ARGS else { panic!() };
+//! assert_eq!(delay.kind, ArgKind::Option);
+//! assert_eq!((delay.short, delay.long), (Some('d'), Some("delay")));
+//! assert_eq!(delay.value_name, "DELAY");
+//! assert_eq!(delay.default_value, Some("500"));
+//! assert_eq!(delay.description, "Delay between blinks in milliseconds");
+//! assert!(leds.optional && !leds.variadic);
+//! ```
 
+#[cfg(feature = "std")]
+pub mod completions;
 mod error;
 mod field;
+#[cfg(feature = "std")]
+pub mod man;
+#[cfg(feature = "std")]
+pub mod markdown;
 mod schmargs;
+pub mod spec;
 #[doc(hidden)]
 pub mod utils;
 mod wrappers;
 
-pub use error::{SchmargsError, StrippedSchmargsError};
+pub use error::{SchmargsError, StrippedSchmargsError, ValueParserError};
 pub use field::SchmargsField;
-pub use schmargs::Schmargs;
+pub use schmargs::{HelpObject, Schmargs};
 pub use schmargs_derive::*;
-pub use wrappers::{ArgsWithHelp, ArgsWithVersion};
+pub use wrappers::{ArgsWithHelp, ArgsWithVersion, HelpRequest};
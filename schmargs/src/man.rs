@@ -0,0 +1,240 @@
+//! Man pages
+//!
+//! [generate] renders a [Schmargs] type as a roff `man(7)` page, with sections built from the
+//! doc comments, value names, and defaults of its arguments. It only needs the type, so it can
+//! be called from a build script.
+//!
+//! # Example
+//!
+//! Write a man page to a file, e.g. from a build script that includes the module the arguments
+//! are defined in
+//!
+//! ```
+//! use schmargs::Schmargs;
+//!
+//! /// Print a friendly greeting
+//! #[derive(Schmargs)]
+//! #[schmargs(name = "hello")]
+//! struct Args<'a> {
+//!     /// Name of the person to greet
+//!     name: &'a str,
+//! }
+//!
+//! let path = std::env::temp_dir().join("hello.1");
+//! let file = std::fs::File::create(&path).unwrap();
+//! schmargs::man::generate::<Args>(file).unwrap();
+//! # std::fs::remove_file(path).unwrap();
+//! ```
+use crate::{
+    spec::{flatten_args, ArgKind, ArgSpec, CommandSpec},
+    Schmargs,
+};
+use std::{fmt::Write as _, io};
+
+/// Write the man page of `S` to `out`. Arguments with `#[arg(hide)]` are left out
+///
+/// # Example
+///
+/// ```
+/// use schmargs::Schmargs;
+///
+/// /// Say hello
+/// #[derive(Schmargs)]
+/// #[schmargs(name = "hello")]
+/// struct Args {
+///     /// Say it loudly
+///     #[arg(short, long)]
+///     loud: bool,
+/// }
+///
+/// let mut page = Vec::new();
+/// schmargs::man::generate::<Args>(&mut page).unwrap();
+/// assert!(String::from_utf8(page).unwrap().starts_with(".TH HELLO 1"));
+/// ```
+pub fn generate<'a, S: Schmargs<'a>>(mut out: impl io::Write) -> io::Result<()> {
+    let mut page = String::new();
+    write_page(&mut page, &CommandSpec::of::<S>(), S::VERSION)
+        .expect("Bug: writing to a String can't fail");
+    out.write_all(page.as_bytes())
+}
+
+fn write_page(f: &mut String, command: &CommandSpec, version: &str) -> std::fmt::Result {
+    let name = escape(command.name);
+    writeln!(
+        f,
+        ".TH {} 1 \"\" \"{}\"",
+        escape(&command.name.to_uppercase()),
+        escape(&format!("{} {version}", command.name)).trim_end()
+    )?;
+
+    writeln!(f, ".SH NAME")?;
+    if command.description.is_empty() {
+        writeln!(f, "{name}")?;
+    } else {
+        writeln!(f, "{name} \\- {}", escape(command.description))?;
+    }
+
+    writeln!(f, ".SH SYNOPSIS")?;
+    write_synopsis(f, command, &[])?;
+
+    if !command.long_description.is_empty() {
+        writeln!(f, ".SH DESCRIPTION")?;
+        write_paragraphs(f, command.long_description, ".PP")?;
+    }
+
+    let args: Vec<_> = flatten_args(command.args)
+        .into_iter()
+        .filter(|(_, arg)| !arg.hidden)
+        .collect();
+    let (positionals, options): (Vec<_>, Vec<_>) = args
+        .iter()
+        .partition(|(_, arg)| arg.kind == ArgKind::Positional);
+    if !positionals.is_empty() {
+        writeln!(f, ".SH ARGUMENTS")?;
+        for (prefix, arg) in positionals {
+            write_entry(f, prefix, arg)?;
+        }
+    }
+    if !options.is_empty() {
+        writeln!(f, ".SH OPTIONS")?;
+        for (prefix, arg) in options {
+            write_entry(f, prefix, arg)?;
+        }
+    }
+
+    if !command.subcommands.is_empty() {
+        writeln!(f, ".SH COMMANDS")?;
+        for subcommand in command.subcommands {
+            writeln!(f, ".TP")?;
+            writeln!(f, "\\fB{}\\fR", escape(subcommand.name))?;
+            write_paragraphs(f, subcommand.description, ".IP")?;
+        }
+    }
+
+    if !version.is_empty() {
+        writeln!(f, ".SH VERSION")?;
+        writeln!(f, "v{}", escape(version))?;
+    }
+    Ok(())
+}
+
+// Write the usage of a command, followed by the usage of each of its subcommands, e.g.
+// "git remote add [OPTIONS] NAME". `parents` are the names of the commands it's nested in
+fn write_synopsis(f: &mut String, command: &CommandSpec, parents: &[&str]) -> std::fmt::Result {
+    let mut path = parents.to_vec();
+    path.push(command.name);
+    if !parents.is_empty() {
+        writeln!(f, ".br")?;
+    }
+    write!(f, "\\fB{}\\fR", escape(&path.join(" ")))?;
+    if !command.usage.is_empty() {
+        write!(f, " {}", escape(command.usage))?;
+    }
+    writeln!(f)?;
+    for subcommand in command.subcommands {
+        write_synopsis(f, subcommand, &path)?;
+    }
+    Ok(())
+}
+
+// Write an argument, e.g. `-o, --output <OUTPUT>` followed by its description
+fn write_entry(f: &mut String, prefix: &str, arg: &ArgSpec) -> std::fmt::Result {
+    writeln!(f, ".TP")?;
+    if arg.kind == ArgKind::Positional {
+        let value_name = format!("\\fI{}\\fR", escape(arg.value_name));
+        let ellipsis = if arg.variadic { "..." } else { "" };
+        if arg.optional {
+            writeln!(f, "[{value_name}]{ellipsis}")?;
+        } else {
+            writeln!(f, "{value_name}{ellipsis}")?;
+        }
+    } else {
+        let mut flags = Vec::new();
+        if let Some(short) = arg.short {
+            flags.push(format!("\\fB{}\\fR", escape(&format!("-{short}"))));
+        }
+        if let Some(long) = arg.long {
+            let negation = if arg.negated_long.is_some() {
+                "[no-]"
+            } else {
+                ""
+            };
+            flags.push(format!(
+                "\\fB{}\\fR",
+                escape(&format!("--{prefix}{negation}{long}"))
+            ));
+        }
+        write!(f, "{}", flags.join(", "))?;
+        match arg.kind {
+            ArgKind::Option => {
+                write!(f, " \\fI{}\\fR", escape(arg.value_name))?;
+                if arg.variadic {
+                    write!(f, "...")?;
+                }
+            }
+            ArgKind::Count => write!(f, "...")?,
+            _ => {}
+        }
+        writeln!(f)?;
+    }
+
+    // Details are appended to the last paragraph, like in the help text
+    let mut description = String::from(arg.long_description);
+    let aliases: Vec<String> = arg
+        .visible_short_aliases
+        .iter()
+        .map(|short| format!("-{short}"))
+        .chain(
+            arg.visible_aliases
+                .iter()
+                .map(|long| format!("--{prefix}{long}")),
+        )
+        .collect();
+    if !aliases.is_empty() {
+        write!(description, " [aliases: {}]", aliases.join(", "))?;
+    }
+    if let Some(env) = arg.env {
+        write!(description, " [env: {env}]")?;
+    }
+    if let Some(default_value) = arg.default_value {
+        write!(description, " [default: {default_value}]")?;
+    }
+    if !arg.possible_values.is_empty() {
+        write!(
+            description,
+            " [possible values: {}]",
+            arg.possible_values.join(", ")
+        )?;
+    }
+    write_paragraphs(f, description.trim_start(), ".IP")
+}
+
+// Write paragraphs separated by blank lines, with `separator` between them
+fn write_paragraphs(f: &mut String, text: &str, separator: &str) -> std::fmt::Result {
+    if text.is_empty() {
+        return Ok(());
+    }
+    for (i, paragraph) in text.split("\n\n").enumerate() {
+        if i != 0 {
+            writeln!(f, "{separator}")?;
+        }
+        writeln!(f, "{}", escape(paragraph))?;
+    }
+    Ok(())
+}
+
+// Escape text for roff. Lines that start with a control character are escaped too
+fn escape(text: &str) -> String {
+    let text = text.replace('\\', "\\e").replace('-', "\\-");
+    let lines: Vec<String> = text
+        .split('\n')
+        .map(|line| {
+            if line.starts_with(['.', '\'']) {
+                format!("\\&{line}")
+            } else {
+                line.into()
+            }
+        })
+        .collect();
+    lines.join("\n")
+}
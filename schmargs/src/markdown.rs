@@ -0,0 +1,236 @@
+//! Markdown reference pages
+//!
+//! [generate] renders a [Schmargs] type as a Markdown page with a section for the command and
+//! each of its subcommands, including nested ones. Each section has a usage block and tables of
+//! arguments, options, and subcommands. The output only changes when the arguments do, so the
+//! page can be committed and diffed in review.
+//!
+//! # Example
+//!
+//! Write the reference page of a command to a file
+//!
+//! ```
+//! use schmargs::Schmargs;
+//!
+//! /// Print a friendly greeting
+//! #[derive(Schmargs)]
+//! #[schmargs(name = "hello")]
+//! struct Args<'a> {
+//!     /// Name of the person to greet
+//!     name: &'a str,
+//! }
+//!
+//! let path = std::env::temp_dir().join("hello.md");
+//! let file = std::fs::File::create(&path).unwrap();
+//! schmargs::markdown::generate::<Args>(file).unwrap();
+//! # std::fs::remove_file(path).unwrap();
+//! ```
+use crate::{
+    spec::{flatten_args, ArgKind, ArgSpec, CommandSpec},
+    Schmargs,
+};
+use std::{fmt::Write as _, io};
+
+/// Write the Markdown reference page of `S` to `out`. Arguments with `#[arg(hide)]` are left out
+///
+/// # Example
+///
+/// ```
+/// use schmargs::Schmargs;
+///
+/// /// Say hello
+/// #[derive(Schmargs)]
+/// #[schmargs(name = "hello")]
+/// struct Args {
+///     /// Say it loudly
+///     #[arg(short, long)]
+///     loud: bool,
+/// }
+///
+/// let mut page = Vec::new();
+/// schmargs::markdown::generate::<Args>(&mut page).unwrap();
+/// let page = String::from_utf8(page).unwrap();
+/// assert!(page.starts_with("# `hello`\n\nSay hello\n"));
+/// assert!(page.contains("| `-l, --loud` | Say it loudly | |\n"));
+/// ```
+pub fn generate<'a, S: Schmargs<'a>>(mut out: impl io::Write) -> io::Result<()> {
+    let mut page = String::new();
+    write_command(&mut page, &CommandSpec::of::<S>(), &[])
+        .expect("Bug: writing to a String can't fail");
+    out.write_all(page.as_bytes())
+}
+
+// Write the section of a command, followed by the sections of its subcommands. `parents` are
+// the names of the commands it's nested in
+fn write_command(f: &mut String, command: &CommandSpec, parents: &[&str]) -> std::fmt::Result {
+    let mut path = parents.to_vec();
+    path.push(command.name);
+    let level = "#".repeat(path.len().min(5));
+
+    if !parents.is_empty() {
+        writeln!(f)?;
+    }
+    writeln!(f, "{level} `{}`", path.join(" "))?;
+    if !command.long_description.is_empty() {
+        writeln!(f)?;
+        writeln!(f, "{}", command.long_description)?;
+    }
+
+    writeln!(f)?;
+    writeln!(f, "{level}# Usage")?;
+    writeln!(f)?;
+    writeln!(f, "```text")?;
+    writeln!(f, "{}", usage(&path, command))?;
+    writeln!(f, "```")?;
+
+    let args: Vec<_> = flatten_args(command.args)
+        .into_iter()
+        .filter(|(_, arg)| !arg.hidden)
+        .collect();
+    let (positionals, options): (Vec<_>, Vec<_>) = args
+        .iter()
+        .partition(|(_, arg)| arg.kind == ArgKind::Positional);
+    for (heading, column, args) in [
+        ("Arguments", "Argument", positionals),
+        ("Options", "Option", options),
+    ] {
+        if args.is_empty() {
+            continue;
+        }
+        writeln!(f)?;
+        writeln!(f, "{level}# {heading}")?;
+        writeln!(f)?;
+        writeln!(f, "| {column} | Description | Default |")?;
+        writeln!(f, "| --- | --- | --- |")?;
+        for (prefix, arg) in args {
+            let default_value = arg
+                .default_value
+                .map(|value| format!(" `{}` ", escape(value)))
+                .unwrap_or_else(|| String::from(" "));
+            writeln!(
+                f,
+                "| `{}` | {} |{default_value}|",
+                escape(&display_arg(prefix, arg)),
+                description(prefix, arg)
+            )?;
+        }
+    }
+
+    if !command.subcommands.is_empty() {
+        writeln!(f)?;
+        writeln!(f, "{level}# Commands")?;
+        writeln!(f)?;
+        writeln!(f, "| Command | Description |")?;
+        writeln!(f, "| --- | --- |")?;
+        for subcommand in command.subcommands {
+            let mut path = path.clone();
+            path.push(subcommand.name);
+            writeln!(
+                f,
+                "| [`{}`](#{}) | {} |",
+                escape(subcommand.name),
+                anchor(&path),
+                escape(subcommand.description)
+            )?;
+        }
+    }
+
+    for subcommand in command.subcommands {
+        write_command(f, subcommand, &path)?;
+    }
+    Ok(())
+}
+
+// Argument as shown in the help text, e.g. "-o, --output <OUTPUT>" or "[FILE]"
+fn display_arg(prefix: &str, arg: &ArgSpec) -> String {
+    let ellipsis = if arg.variadic { "..." } else { "" };
+    match arg.kind {
+        ArgKind::Positional if arg.optional => format!("[{}]{ellipsis}", arg.value_name),
+        ArgKind::Positional => format!("{}{ellipsis}", arg.value_name),
+        _ => {
+            let mut flags = Vec::new();
+            if let Some(short) = arg.short {
+                flags.push(format!("-{short}"));
+            }
+            if let Some(long) = arg.long {
+                let negation = if arg.negated_long.is_some() {
+                    "[no-]"
+                } else {
+                    ""
+                };
+                flags.push(format!("--{prefix}{negation}{long}"));
+            }
+            let mut string = flags.join(", ");
+            match arg.kind {
+                ArgKind::Option => write!(string, " <{}>{ellipsis}", arg.value_name)
+                    .expect("Bug: writing to a String can't fail"),
+                ArgKind::Count => string.push_str("..."),
+                _ => {}
+            }
+            string
+        }
+    }
+}
+
+// Description of an argument for a table cell, followed by its aliases, environment variable,
+// and possible values
+fn description(prefix: &str, arg: &ArgSpec) -> String {
+    let mut description = escape(arg.long_description);
+    let mut details = Vec::new();
+    let aliases: Vec<String> = arg
+        .visible_short_aliases
+        .iter()
+        .map(|short| format!("`-{short}`"))
+        .chain(
+            arg.visible_aliases
+                .iter()
+                .map(|long| format!("`--{prefix}{long}`")),
+        )
+        .collect();
+    if !aliases.is_empty() {
+        details.push(format!("Aliases: {}", aliases.join(", ")));
+    }
+    if let Some(env) = arg.env {
+        details.push(format!("Environment variable: `{env}`"));
+    }
+    if !arg.possible_values.is_empty() {
+        let values: Vec<String> = arg
+            .possible_values
+            .iter()
+            .map(|value| format!("`{}`", escape(value)))
+            .collect();
+        details.push(format!("Possible values: {}", values.join(", ")));
+    }
+    for detail in details {
+        if !description.is_empty() {
+            description.push_str("<br><br>");
+        }
+        description.push_str(&detail);
+    }
+    description
+}
+
+// Usage text of a command, e.g. "git remote add [OPTIONS] NAME". `path` holds the names of the
+// command and the commands it's nested in
+fn usage(path: &[&str], command: &CommandSpec) -> String {
+    let mut usage = path.join(" ");
+    if !command.usage.is_empty() {
+        usage.push(' ');
+        usage.push_str(command.usage);
+    }
+    usage
+}
+
+// Anchor of the heading of a command, as generated by GitHub, e.g. "git-remote-add"
+fn anchor(path: &[&str]) -> String {
+    path.join("-")
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_alphanumeric() || *c == '-' || *c == '_')
+        .collect()
+}
+
+// Escape text for a table cell
+fn escape(text: &str) -> String {
+    text.replace('|', "\\|").replace("\n\n", "<br><br>")
+}
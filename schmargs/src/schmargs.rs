@@ -1,4 +1,8 @@
-use crate::SchmargsError;
+use crate::{
+    spec::{ArgSpec, CommandSpec},
+    utils::{default_help_width, write_help_kind},
+    HelpRequest, SchmargsError,
+};
 use core::fmt;
 use core::marker::PhantomData;
 
@@ -16,9 +20,28 @@ pub trait Schmargs<'a>: Sized {
     /// Command version
     const VERSION: &'static str;
 
-    /// Command description
+    /// Command description. This is the first paragraph of the doc comment
     const DESCRIPTION: &'static str;
 
+    /// Long command description. This is the whole doc comment
+    const LONG_DESCRIPTION: &'static str = Self::DESCRIPTION;
+
+    /// Long flag that shows the help text of arguments with `#[arg(hide)]`, e.g. `--help-all`.
+    /// This is set with `#[schmargs(show_hidden_with = "--help-all")]`, and handled by
+    /// [ArgsWithHelp](crate::ArgsWithHelp)
+    const SHOW_HIDDEN_WITH: Option<&'static str> = None;
+
+    /// Description of each argument, in the order of the fields. Fields with `#[arg(flatten)]`
+    /// are described by an [ArgKind::Flatten](crate::spec::ArgKind::Flatten) entry. Wrappers like
+    /// [ArgsWithHelp](crate::ArgsWithHelp) add an entry for their flag
+    ///
+    /// This is empty for enums, whose arguments are in [Schmargs::SUBCOMMANDS], and for types that
+    /// implement [Schmargs] by hand and don't set it
+    const ARGS: &'static [ArgSpec] = &[];
+
+    /// Description of each subcommand, if this is derived on an enum
+    const SUBCOMMANDS: &'static [CommandSpec] = &[];
+
     /// Write help text to `f`
     /// Returns the indent used, which will be greater than or equal to `min_indent`
     ///
@@ -29,6 +52,91 @@ pub trait Schmargs<'a>: Sized {
         min_indent: usize,
     ) -> Result<usize, fmt::Error>;
 
+    /// Like [Schmargs::write_help_with_min_indent], but write the long help text, which includes
+    /// the whole doc comment of the command and each argument
+    ///
+    /// Unless you're implementing [Schmargs], you most likely want to use the
+    /// [Schmargs::long_help] method
+    fn write_long_help_with_min_indent(
+        f: impl fmt::Write,
+        min_indent: usize,
+    ) -> Result<usize, fmt::Error> {
+        Self::write_help_with_min_indent(f, min_indent)
+    }
+
+    /// Like [Schmargs::write_help_with_min_indent], but include arguments with `#[arg(hide)]`
+    ///
+    /// Unless you're implementing [Schmargs], you most likely want to use the
+    /// [Schmargs::help_all] method
+    fn write_help_all_with_min_indent(
+        f: impl fmt::Write,
+        min_indent: usize,
+    ) -> Result<usize, fmt::Error> {
+        Self::write_help_with_min_indent(f, min_indent)
+    }
+
+    /// Like [Schmargs::write_help_with_min_indent], but wrap lines at `width` columns, e.g. to fit
+    /// a `no_std` console. Lines are broken at spaces, and continuation lines of descriptions are
+    /// aligned with their first line
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use schmargs::Schmargs;
+    ///
+    /// /// Fake program
+    /// #[derive(Schmargs)]
+    /// #[schmargs(name = "fake")]
+    /// struct Args {
+    ///     /// Pretend to do something useful, for a while
+    ///     #[arg(short, long)]
+    ///     pretend: bool,
+    /// }
+    ///
+    /// let mut help = String::new();
+    /// Args::write_help_with_width(&mut help, 0, 30).unwrap();
+    /// assert_eq!(
+    ///     help,
+    ///     "Fake program
+    ///
+    /// Usage: fake [OPTIONS]
+    ///
+    /// Options:
+    /// -p, --pretend Pretend to do
+    ///               something
+    ///               useful, for a
+    ///               while"
+    /// );
+    /// ```
+    fn write_help_with_width(
+        f: impl fmt::Write,
+        min_indent: usize,
+        width: usize,
+    ) -> Result<usize, fmt::Error> {
+        let _ = width;
+        Self::write_help_with_min_indent(f, min_indent)
+    }
+
+    /// Like [Schmargs::write_long_help_with_min_indent], but wrap lines at `width` columns
+    fn write_long_help_with_width(
+        f: impl fmt::Write,
+        min_indent: usize,
+        width: usize,
+    ) -> Result<usize, fmt::Error> {
+        let _ = width;
+        Self::write_long_help_with_min_indent(f, min_indent)
+    }
+
+    /// Like [Schmargs::write_help_all_with_min_indent], but wrap lines at `width` columns
+    fn write_help_all_with_width(
+        f: impl fmt::Write,
+        min_indent: usize,
+        width: usize,
+    ) -> Result<usize, fmt::Error> {
+        let _ = width;
+        Self::write_help_all_with_min_indent(f, min_indent)
+    }
+
     /// Get help object
     ///
     /// # Example
@@ -43,7 +151,83 @@ pub trait Schmargs<'a>: Sized {
     /// println!("{}", Args::help());
     /// ```
     fn help() -> HelpObject<Self> {
-        HelpObject(PhantomData)
+        HelpObject {
+            kind: HelpKind::Short,
+            width: None,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Get long help object
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use schmargs::Schmargs;
+    ///
+    /// /// Fake program
+    /// ///
+    /// /// It doesn't do anything.
+    /// #[derive(Schmargs)]
+    /// struct Args {}
+    ///
+    /// println!("{}", Args::long_help());
+    /// ```
+    fn long_help() -> HelpObject<Self> {
+        HelpObject {
+            kind: HelpKind::Long,
+            width: None,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Get help object that includes arguments with `#[arg(hide)]`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use schmargs::Schmargs;
+    ///
+    /// /// Fake program
+    /// #[derive(Schmargs)]
+    /// struct Args {
+    ///     /// Print debug info
+    ///     #[arg(long, hide)]
+    ///     debug: bool,
+    /// }
+    ///
+    /// println!("{}", Args::help_all());
+    /// ```
+    fn help_all() -> HelpObject<Self> {
+        HelpObject {
+            kind: HelpKind::All,
+            width: None,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Write the help text of the subcommand at `path`, which holds the index into
+    /// [Schmargs::SUBCOMMANDS] of each nested subcommand. `name` is shown in place of
+    /// [Schmargs::NAME] in the usage text, e.g. `git remote`. Returns the indent used, and whether
+    /// the text ends with the "Options:" section, so wrappers know where to add their options
+    #[doc(hidden)]
+    fn write_subcommand_help(
+        path: &[u16],
+        name: &dyn fmt::Display,
+        f: impl fmt::Write,
+        min_indent: usize,
+        width: usize,
+        kind: HelpKind,
+    ) -> Result<(usize, bool), fmt::Error> {
+        let _ = (path, name);
+        Ok((write_help_kind::<Self>(f, min_indent, width, kind)?, true))
+    }
+
+    /// Help text asked for by the parsed arguments, e.g. when they're wrapped in
+    /// [ArgsWithHelp](crate::ArgsWithHelp)
+    #[doc(hidden)]
+    fn help_request(&self) -> Option<HelpRequest> {
+        None
     }
 
     /// Construct from an iterator of arguments
@@ -73,11 +257,50 @@ pub trait Schmargs<'a>: Sized {
     }
 }
 
-pub struct HelpObject<T>(PhantomData<T>);
+/// Help text that can be displayed, returned by [Schmargs::help] and similar methods
+pub struct HelpObject<T> {
+    kind: HelpKind,
+    // Falls back on `default_help_width` when the help is displayed
+    width: Option<usize>,
+    _marker: PhantomData<T>,
+}
+
+impl<T> HelpObject<T> {
+    /// Wrap lines at `width` columns. By default, lines are wrapped at the width set by the
+    /// `COLUMNS` environment variable with the `std` feature, and aren't wrapped otherwise
+    pub fn with_width(mut self, width: usize) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    /// Wrap lines at the width of the terminal, as set by the `COLUMNS` environment variable.
+    /// Lines aren't wrapped if it's unset, empty, or `0`. This is the default, and undoes
+    /// [HelpObject::with_width]
+    #[cfg(feature = "std")]
+    pub fn with_terminal_width(mut self) -> Self {
+        self.width = None;
+        self
+    }
+}
+
+/// Which help text to write
+#[doc(hidden)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum HelpKind {
+    Short,
+    Long,
+    /// Short help, including hidden arguments
+    All,
+}
 
 impl<'a, S: Schmargs<'a>> fmt::Display for HelpObject<S> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        S::write_help_with_min_indent(f, 0)?;
+        let width = self.width.unwrap_or_else(default_help_width);
+        match self.kind {
+            HelpKind::Short => S::write_help_with_width(f, 0, width)?,
+            HelpKind::Long => S::write_long_help_with_width(f, 0, width)?,
+            HelpKind::All => S::write_help_all_with_width(f, 0, width)?,
+        };
         Ok(())
     }
 }
@@ -0,0 +1,168 @@
+//! Static descriptions of arguments and subcommands
+//!
+//! The derive macro describes the arguments of a type in [Schmargs::ARGS], and its subcommands in
+//! [Schmargs::SUBCOMMANDS]. The descriptions are `'static` and need neither `std` nor `alloc`, so
+//! they can live in flash. Tools like [completions](crate::completions) are generated from them.
+use crate::{utils::usage_after_name, Schmargs};
+
+/// Kind of an argument
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ArgKind {
+    /// A flag that doesn't take a value, e.g. `--verbose`
+    Flag,
+    /// A flag that counts its occurrences, e.g. `-vvv`
+    Count,
+    /// A flag that takes a value, e.g. `--output <OUTPUT>`
+    Option,
+    /// A positional argument
+    Positional,
+    /// The arguments of a field with `#[arg(flatten)]`, whose long flags are prefixed with
+    /// `prefix`
+    Flatten {
+        /// Prefix of the long flags, e.g. "db-"
+        prefix: &'static str,
+        /// Arguments of the flattened field
+        args: &'static [ArgSpec],
+    },
+}
+
+/// What kind of value an argument takes, which shells use to complete it
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ValueHint {
+    /// Nothing is known about the value
+    #[default]
+    Unknown,
+    /// Path to a file or directory
+    AnyPath,
+    /// Path to a file
+    FilePath,
+    /// Path to a directory
+    DirPath,
+    /// Name of a host
+    Hostname,
+    /// Name of a user
+    Username,
+    /// Name of a command
+    CommandName,
+}
+
+/// Description of an argument
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ArgSpec {
+    /// Kind of the argument
+    pub kind: ArgKind,
+    /// Name of the field
+    pub name: &'static str,
+    /// Short flag
+    pub short: Option<char>,
+    /// Long flag, without the leading "--"
+    pub long: Option<&'static str>,
+    /// Short aliases that are shown in the help text
+    pub visible_short_aliases: &'static [char],
+    /// Long aliases that are shown in the help text, without the leading "--"
+    pub visible_aliases: &'static [&'static str],
+    /// Negated long flag without the leading "--", e.g. "no-color", if the argument is
+    /// `negatable`
+    pub negated_long: Option<&'static str>,
+    /// Name of the value, e.g. "OUTPUT"
+    pub value_name: &'static str,
+    /// What kind of value the argument takes
+    pub value_hint: ValueHint,
+    /// Values the argument can take, if there's a limited set of them
+    pub possible_values: &'static [&'static str],
+    /// Default value, if it's set with `default_value = <expression>`
+    pub default_value: Option<&'static str>,
+    /// Environment variable the argument falls back on
+    pub env: Option<&'static str>,
+    /// First paragraph of the doc comment
+    pub description: &'static str,
+    /// Whole doc comment
+    pub long_description: &'static str,
+    /// Can the argument be left out
+    pub optional: bool,
+    /// Does the argument take more than one value
+    pub variadic: bool,
+    /// Is the argument left out of the help and usage text
+    pub hidden: bool,
+}
+
+impl ArgSpec {
+    /// A positional argument with no name. Useful as a base for struct update syntax
+    pub const DEFAULT: Self = Self {
+        kind: ArgKind::Positional,
+        name: "",
+        short: None,
+        long: None,
+        visible_short_aliases: &[],
+        visible_aliases: &[],
+        negated_long: None,
+        value_name: "",
+        value_hint: ValueHint::Unknown,
+        possible_values: &[],
+        default_value: None,
+        env: None,
+        description: "",
+        long_description: "",
+        optional: false,
+        variadic: false,
+        hidden: false,
+    };
+
+    /// Does the argument take a value
+    pub const fn takes_value(&self) -> bool {
+        matches!(self.kind, ArgKind::Option | ArgKind::Positional)
+    }
+}
+
+/// Description of a command or subcommand
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CommandSpec {
+    /// Name used on the command line
+    pub name: &'static str,
+    /// First paragraph of the doc comment
+    pub description: &'static str,
+    /// Whole doc comment
+    pub long_description: &'static str,
+    /// Usage text after the name of the command, e.g. `[OPTIONS] NAME` for
+    /// `git remote add [OPTIONS] NAME`. This is [Schmargs::USAGE] without [Schmargs::NAME]
+    pub usage: &'static str,
+    /// Arguments of the command
+    pub args: &'static [ArgSpec],
+    /// Subcommands of the command
+    pub subcommands: &'static [CommandSpec],
+}
+
+impl CommandSpec {
+    /// Describe a [Schmargs] type
+    pub const fn of<'a, S: Schmargs<'a>>() -> Self {
+        Self {
+            name: S::NAME,
+            description: S::DESCRIPTION,
+            long_description: S::LONG_DESCRIPTION,
+            usage: usage_after_name(S::USAGE, S::NAME),
+            args: S::ARGS,
+            subcommands: S::SUBCOMMANDS,
+        }
+    }
+}
+
+/// Arguments of `args`, including those of flattened fields, along with the prefix of their long
+/// flags
+#[cfg(feature = "std")]
+pub(crate) fn flatten_args(args: &'static [ArgSpec]) -> Vec<(String, &'static ArgSpec)> {
+    fn visit(args: &'static [ArgSpec], prefix: &str, out: &mut Vec<(String, &'static ArgSpec)>) {
+        for arg in args {
+            match arg.kind {
+                ArgKind::Flatten {
+                    prefix: inner,
+                    args,
+                } => visit(args, &(String::from(prefix) + inner), out),
+                _ => out.push((prefix.into(), arg)),
+            }
+        }
+    }
+
+    let mut out = Vec::new();
+    visit(args, "", &mut out);
+    out
+}
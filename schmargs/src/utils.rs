@@ -1,4 +1,1162 @@
 //! Parsing utilities for internal use
+use crate::error::ValueList;
+pub use crate::schmargs::HelpKind;
+use crate::spec::{ArgKind, ArgSpec, CommandSpec};
+use crate::{Schmargs, SchmargsError};
+use core::fmt;
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "alloc")]
+use alloc::{string::String, vec::Vec};
+
+/// A string type that can be split without losing its type
+#[doc(hidden)]
+pub trait StringLike: Sized + AsRef<str> {
+    /// Split by commas
+    fn split_commas(self) -> impl Iterator<Item = Self>;
+    /// Split at the first occurrence of `delimiter`, removing it
+    fn split_once(self, delimiter: char) -> (Self, Option<Self>);
+    /// Split at byte `index`
+    fn split_at(self, index: usize) -> (Self, Self);
+}
+
+impl StringLike for &str {
+    fn split_commas(self) -> impl Iterator<Item = Self> {
+        self.split(',')
+    }
+
+    fn split_once(self, delimiter: char) -> (Self, Option<Self>) {
+        match str::split_once(self, delimiter) {
+            Some((before, after)) => (before, Some(after)),
+            None => (self, None),
+        }
+    }
+
+    fn split_at(self, index: usize) -> (Self, Self) {
+        str::split_at(self, index)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl StringLike for String {
+    fn split_commas(self) -> impl Iterator<Item = Self> {
+        let val: &str = self.as_ref();
+        let val = val.split(',').map(Self::from);
+        let val: Vec<String> = val.collect();
+        val.into_iter()
+    }
+
+    fn split_once(mut self, delimiter: char) -> (Self, Option<Self>) {
+        match self.find(delimiter) {
+            Some(index) => {
+                let after = self.split_off(index + delimiter.len_utf8());
+                self.truncate(index);
+                (self, Some(after))
+            }
+            None => (self, None),
+        }
+    }
+
+    fn split_at(mut self, index: usize) -> (Self, Self) {
+        let after = self.split_off(index);
+        (self, after)
+    }
+}
+
+/// Split a long flag into the flag and its value, e.g. `--group=8` into `--group` and `8`
+#[doc(hidden)]
+pub fn split_long_flag<T: StringLike>(flag: T) -> (T, Option<T>) {
+    flag.split_once('=')
+}
+
+/// An iterator over a cluster of short flags, e.g. `-xvf`
+///
+/// Following getopt, the rest of the cluster can be taken as the value of an option, so `-n5` is
+/// `-n 5` and `-xvfarchive.tar` is `-x -v -f archive.tar`
+#[doc(hidden)]
+pub struct ShortCluster<T> {
+    flags: Option<T>,
+    index: usize,
+}
+
+impl<T: StringLike> ShortCluster<T> {
+    /// Construct from a cluster that starts with "-"
+    pub fn new(flags: T) -> Self {
+        Self {
+            flags: Some(flags),
+            index: "-".len(),
+        }
+    }
+
+    /// Take the rest of the cluster as a value, if there is any. This ends iteration
+    pub fn take_value(&mut self) -> Option<T> {
+        let flags = self.flags.take()?;
+        if self.index < flags.as_ref().len() {
+            Some(flags.split_at(self.index).1)
+        } else {
+            None
+        }
+    }
+}
+
+impl<T: StringLike> Iterator for ShortCluster<T> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        let flags = self.flags.as_ref()?;
+        let short = flags.as_ref()[self.index..].chars().next()?;
+        self.index += short.len_utf8();
+        Some(short)
+    }
+}
+
+/// Implementation detail of `#[arg(flatten)]`, which lets the arguments of one [Schmargs] type be
+/// parsed as part of another
+#[doc(hidden)]
+pub trait Flatten<'a>: Schmargs<'a> {
+    /// Arguments that have been parsed so far
+    type State;
+
+    /// Number of positional arguments, including those of flattened fields
+    const POSITIONALS: usize;
+    /// Positional arguments as shown in the usage text, e.g. " FILE [OUTPUT]"
+    const USAGE_POSITIONALS: &'static str;
+    /// Should "[OPTIONS]" be shown in the usage text
+    const USAGE_OPTIONS: bool;
+    /// Number of positional arguments with `#[arg(hide)]`, including those of flattened fields
+    const HIDDEN_POSITIONALS: usize;
+    /// Should the "Options:" heading be shown in the help text. This is false if all flags and
+    /// options are in groups, which have their own headings, or hidden
+    const HELP_OPTIONS: bool;
+    /// Are there flags or options with `#[arg(hide)]` that aren't in groups
+    const HIDDEN_OPTIONS: bool;
+    /// All short and long flags, including those of flattened fields
+    const FLAGS: FlagSet;
+    /// Required options, which are shown in the usage text
+    const REQUIRED: RequiredOptions;
+    /// Evaluating this fails to compile if any flags conflict
+    const VALIDATE: () = if let Some(conflict) = <Self as Flatten<'a>>::FLAGS.conflict() {
+        panic!("{}", conflict.as_str())
+    };
+
+    /// Construct empty state
+    fn init() -> Self::State;
+
+    /// Parse a long flag, without its leading "--". Options take `value` if it's present, which
+    /// is the part after the "=" in `--long=value`. Returns false if there is no such flag
+    fn parse_long<I: Iterator<Item = Self::Item>>(
+        state: &mut Self::State,
+        long: &str,
+        value: &mut Option<Self::Item>,
+        args: &mut DumbIterator<Self::Item, I>,
+    ) -> Result<bool, SchmargsError<Self::Item>>;
+
+    /// Parse a short flag. Options take the rest of `cluster` as their value if there is any.
+    /// Returns false if there is no such flag
+    fn parse_short<I: Iterator<Item = Self::Item>>(
+        state: &mut Self::State,
+        short: char,
+        cluster: &mut ShortCluster<Self::Item>,
+        args: &mut DumbIterator<Self::Item, I>,
+    ) -> Result<bool, SchmargsError<Self::Item>>;
+
+    /// Parse the positional argument at `position`. Returns the value back if there is no such
+    /// positional argument
+    fn parse_positional<I: Iterator<Item = Self::Item>>(
+        state: &mut Self::State,
+        position: usize,
+        value: Self::Item,
+        args: &mut DumbIterator<Self::Item, I>,
+    ) -> Result<Option<Self::Item>, SchmargsError<Self::Item>>;
+
+    /// Construct from the parsed state
+    fn finish(state: Self::State) -> Result<Self, SchmargsError<Self::Item>>;
+
+    /// Minimum indent needed to fit all help entries. Hidden arguments only count if `hidden` is
+    /// true
+    fn help_width(prefix: FlagPrefix, hidden: bool) -> usize;
+
+    /// Write the help entries of positional arguments. If `long` is true, the whole doc comment
+    /// of each argument is written. If `hidden` is true, arguments with `#[arg(hide)]` are written
+    /// too
+    fn write_arguments(
+        f: &mut HelpWriter<impl fmt::Write>,
+        min_indent: usize,
+        long: bool,
+        hidden: bool,
+    ) -> fmt::Result;
+
+    /// Write the help entries of flags and options that aren't in groups
+    fn write_options(
+        f: &mut HelpWriter<impl fmt::Write>,
+        min_indent: usize,
+        prefix: FlagPrefix,
+        long: bool,
+        hidden: bool,
+    ) -> fmt::Result;
+
+    /// Write the heading and help entries of each group
+    fn write_groups(
+        f: &mut HelpWriter<impl fmt::Write>,
+        min_indent: usize,
+        prefix: FlagPrefix,
+        long: bool,
+        hidden: bool,
+    ) -> fmt::Result;
+}
+
+/// Implemented by [Schmargs] types without a `-h` flag, which can be wrapped in
+/// [ArgsWithHelp](crate::ArgsWithHelp). The derive macro implements this and the other marker
+/// traits, so a flag that would shadow a wrapper's is reported when the crate is checked
+#[doc(hidden)]
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` has a `-h` flag, which conflicts with the one `ArgsWithHelp` adds",
+    label = "`-h` is used by `{Self}`",
+    note = "rename the flag, or don't wrap `{Self}` in `ArgsWithHelp`"
+)]
+pub trait NoHelpShort {}
+
+/// Implemented by [Schmargs] types without a `--help` flag
+#[doc(hidden)]
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` has a `--help` flag, which conflicts with the one `ArgsWithHelp` adds",
+    label = "`--help` is used by `{Self}`",
+    note = "rename the flag, or don't wrap `{Self}` in `ArgsWithHelp`"
+)]
+pub trait NoHelpLong {}
+
+/// Implemented by [Schmargs] types without a `-v` flag, which can be wrapped in
+/// [ArgsWithVersion](crate::ArgsWithVersion)
+#[doc(hidden)]
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` has a `-v` flag, which conflicts with the one `ArgsWithVersion` adds",
+    label = "`-v` is used by `{Self}`",
+    note = "rename the flag, or don't wrap `{Self}` in `ArgsWithVersion`"
+)]
+pub trait NoVersionShort {}
+
+/// Implemented by [Schmargs] types without a `--version` flag
+#[doc(hidden)]
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` has a `--version` flag, which conflicts with the one `ArgsWithVersion` adds",
+    label = "`--version` is used by `{Self}`",
+    note = "rename the flag, or don't wrap `{Self}` in `ArgsWithVersion`"
+)]
+pub trait NoVersionLong {}
+
+/// Short and long flags of a [Schmargs] type
+#[doc(hidden)]
+pub struct FlagSet {
+    /// Short flags, along with the field they belong to
+    pub shorts: &'static [(char, &'static str)],
+    /// Long flags without the leading "--", along with the field they belong to
+    pub longs: &'static [(&'static str, &'static str)],
+    /// Flags of flattened fields, along with their prefix and field
+    pub flattened: &'static [(&'static str, &'static str, &'static FlagSet)],
+}
+
+// Capacity of the message returned by `FlagSet::conflict`
+const CONFLICT_CAPACITY: usize = 256;
+
+impl FlagSet {
+    /// No flags
+    pub const EMPTY: Self = Self {
+        shorts: &[],
+        longs: &[],
+        flattened: &[],
+    };
+
+    /// Returns true if there are no flags
+    pub const fn is_empty(&self) -> bool {
+        self.num_shorts() == 0 && self.num_longs() == 0
+    }
+
+    const fn num_shorts(&self) -> usize {
+        let mut count = self.shorts.len();
+        let mut i = 0;
+        while i < self.flattened.len() {
+            count += self.flattened[i].2.num_shorts();
+            i += 1;
+        }
+        count
+    }
+
+    const fn num_longs(&self) -> usize {
+        let mut count = self.longs.len();
+        let mut i = 0;
+        while i < self.flattened.len() {
+            count += self.flattened[i].2.num_longs();
+            i += 1;
+        }
+        count
+    }
+
+    const fn short(&self, mut index: usize) -> char {
+        if index < self.shorts.len() {
+            return self.shorts[index].0;
+        }
+        index -= self.shorts.len();
+        let mut i = 0;
+        loop {
+            let flattened = self.flattened[i].2;
+            if index < flattened.num_shorts() {
+                return flattened.short(index);
+            }
+            index -= flattened.num_shorts();
+            i += 1;
+        }
+    }
+
+    // Length of a long flag, including prefixes
+    const fn long_len(&self, mut index: usize) -> usize {
+        if index < self.longs.len() {
+            return self.longs[index].0.len();
+        }
+        index -= self.longs.len();
+        let mut i = 0;
+        loop {
+            let (prefix, _, flattened) = self.flattened[i];
+            if index < flattened.num_longs() {
+                return prefix.len() + flattened.long_len(index);
+            }
+            index -= flattened.num_longs();
+            i += 1;
+        }
+    }
+
+    // Byte of a long flag, including prefixes
+    const fn long_byte(&self, mut index: usize, byte: usize) -> u8 {
+        if index < self.longs.len() {
+            return self.longs[index].0.as_bytes()[byte];
+        }
+        index -= self.longs.len();
+        let mut i = 0;
+        loop {
+            let (prefix, _, flattened) = self.flattened[i];
+            if index < flattened.num_longs() {
+                if byte < prefix.len() {
+                    return prefix.as_bytes()[byte];
+                }
+                return flattened.long_byte(index, byte - prefix.len());
+            }
+            index -= flattened.num_longs();
+            i += 1;
+        }
+    }
+
+    const fn longs_equal(&self, a: usize, b: usize) -> bool {
+        let len = self.long_len(a);
+        if len != self.long_len(b) {
+            return false;
+        }
+        let mut byte = 0;
+        while byte < len {
+            if self.long_byte(a, byte) != self.long_byte(b, byte) {
+                return false;
+            }
+            byte += 1;
+        }
+        true
+    }
+
+    // Append a long flag, including prefixes
+    const fn push_long<const N: usize>(
+        &self,
+        string: ConstStr<N>,
+        mut index: usize,
+    ) -> ConstStr<N> {
+        if index < self.longs.len() {
+            return string.push(self.longs[index].0);
+        }
+        index -= self.longs.len();
+        let mut i = 0;
+        loop {
+            let (prefix, _, flattened) = self.flattened[i];
+            if index < flattened.num_longs() {
+                return flattened.push_long(string.push(prefix), index);
+            }
+            index -= flattened.num_longs();
+            i += 1;
+        }
+    }
+
+    // Append the field a flag belongs to, preceded by the flattened fields it's in, e.g.
+    // "common.verbose"
+    const fn push_field<const N: usize>(
+        &self,
+        mut string: ConstStr<N>,
+        long: bool,
+        mut index: usize,
+    ) -> ConstStr<N> {
+        let len = if long {
+            self.longs.len()
+        } else {
+            self.shorts.len()
+        };
+        if index < len {
+            return string.push(if long {
+                self.longs[index].1
+            } else {
+                self.shorts[index].1
+            });
+        }
+        index -= len;
+        let mut i = 0;
+        loop {
+            let (_, field, flattened) = self.flattened[i];
+            let len = if long {
+                flattened.num_longs()
+            } else {
+                flattened.num_shorts()
+            };
+            if index < len {
+                // Wrappers flatten the type they wrap into an unnamed field
+                if !field.is_empty() {
+                    string = string.push(field).push(".");
+                }
+                return flattened.push_field(string, long, index);
+            }
+            index -= len;
+            i += 1;
+        }
+    }
+
+    // Message describing flags `a` and `b`, which are the same
+    const fn conflict_message(
+        &self,
+        long: bool,
+        a: usize,
+        b: usize,
+    ) -> ConstStr<CONFLICT_CAPACITY> {
+        let mut string = ConstStr::new();
+        if long {
+            string = self.push_long(string.push("Flag `--"), a);
+        } else {
+            string = string
+                .push("Flag `-")
+                .push(self.short(a).encode_utf8(&mut [0; 4]));
+        }
+        string = self.push_field(string.push("` is used by both `"), long, a);
+        self.push_field(string.push("` and `"), long, b).push("`")
+    }
+
+    /// Returns a message describing the first two flags that are the same, if any, e.g.
+    /// "Flag `-v` is used by both `version` and `common.verbose`"
+    pub const fn conflict(&self) -> Option<ConstStr<CONFLICT_CAPACITY>> {
+        let mut a = 0;
+        while a < self.num_shorts() {
+            let mut b = a + 1;
+            while b < self.num_shorts() {
+                if self.short(a) == self.short(b) {
+                    return Some(self.conflict_message(false, a, b));
+                }
+                b += 1;
+            }
+            a += 1;
+        }
+
+        let mut a = 0;
+        while a < self.num_longs() {
+            let mut b = a + 1;
+            while b < self.num_longs() {
+                if self.longs_equal(a, b) {
+                    return Some(self.conflict_message(true, a, b));
+                }
+                b += 1;
+            }
+            a += 1;
+        }
+
+        None
+    }
+}
+
+/// Required options of a [Schmargs] type
+#[doc(hidden)]
+pub struct RequiredOptions {
+    /// Usage of each option, split where the prefix goes, e.g. `("--", "name <NAME>")` or
+    /// `("-", "n <N>")`
+    pub options: &'static [(&'static str, &'static str)],
+    /// Required options of flattened fields, along with their prefix
+    pub flattened: &'static [(&'static str, &'static RequiredOptions)],
+}
+
+/// Prefix of the long flags of a flattened field, which is prepended to the prefixes of any
+/// flattened fields it contains
+#[doc(hidden)]
+#[derive(Copy, Clone)]
+pub struct FlagPrefix<'p> {
+    parent: Option<&'p FlagPrefix<'p>>,
+    prefix: &'static str,
+}
+
+impl<'p> FlagPrefix<'p> {
+    /// No prefix
+    pub const NONE: FlagPrefix<'static> = FlagPrefix {
+        parent: None,
+        prefix: "",
+    };
+
+    /// Append a prefix
+    pub fn push(&'p self, prefix: &'static str) -> Self {
+        Self {
+            parent: Some(self),
+            prefix,
+        }
+    }
+
+    /// Total length of the prefix
+    pub fn len(&self) -> usize {
+        self.prefix.len() + self.parent.map(|parent| parent.len()).unwrap_or(0)
+    }
+
+    /// Returns true if there's no prefix
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl fmt::Display for FlagPrefix<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(parent) = self.parent {
+            write!(f, "{parent}")?;
+        }
+        write!(f, "{}", self.prefix)
+    }
+}
+
+/// Width of help text that isn't wrapped
+#[doc(hidden)]
+pub const NO_WRAP: usize = usize::MAX;
+
+/// Writer of help text that wraps lines at a given width
+///
+/// Wrapping is enabled with [HelpWriter::wrap], which sets the indent of continuation lines.
+/// Lines are only broken at spaces, so words longer than the width aren't broken
+#[doc(hidden)]
+pub struct HelpWriter<W> {
+    inner: W,
+    width: usize,
+    // Column of the cursor
+    column: usize,
+    // Indent of continuation lines, or `None` if wrapping is disabled
+    indent: Option<usize>,
+    // Spaces that haven't been written yet, because the line might be broken there
+    spaces: usize,
+    // Was the last character written part of a word
+    in_word: bool,
+}
+
+impl<W: fmt::Write> HelpWriter<W> {
+    /// Construct from the writer to wrap. Lines are wrapped at `width` columns
+    pub fn new(inner: W, width: usize) -> Self {
+        Self {
+            inner,
+            width,
+            column: 0,
+            indent: None,
+            spaces: 0,
+            in_word: false,
+        }
+    }
+
+    /// Wrap what's written after this, with continuation lines indented by `indent`. Wrapping is
+    /// disabled if `indent` is `None`
+    pub fn wrap(&mut self, indent: Option<usize>) -> fmt::Result {
+        self.write_spaces()?;
+        self.indent = indent;
+        Ok(())
+    }
+
+    /// Lets `write!` be used without importing [fmt::Write]
+    pub fn write_fmt(&mut self, args: fmt::Arguments<'_>) -> fmt::Result {
+        fmt::Write::write_fmt(self, args)
+    }
+
+    fn write_spaces(&mut self) -> fmt::Result {
+        for _ in 0..self.spaces {
+            self.inner.write_char(' ')?;
+        }
+        self.column += self.spaces;
+        self.spaces = 0;
+        Ok(())
+    }
+
+    // Write part of a word, breaking the line before it if it's the start of a word that doesn't
+    // fit
+    fn write_word(&mut self, word: &str, indent: usize) -> fmt::Result {
+        let len = word.chars().count();
+        if !self.in_word
+            && self.spaces != 0
+            && self.column > indent
+            && self.column + self.spaces + len > self.width
+        {
+            self.inner.write_char('\n')?;
+            for _ in 0..indent {
+                self.inner.write_char(' ')?;
+            }
+            self.column = indent;
+            self.spaces = 0;
+        }
+        self.write_spaces()?;
+        self.inner.write_str(word)?;
+        self.column += len;
+        self.in_word = true;
+        Ok(())
+    }
+}
+
+impl<W: fmt::Write> fmt::Write for HelpWriter<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let indent = match self.indent {
+            Some(indent) if self.width != NO_WRAP => indent,
+            _ => {
+                self.write_spaces()?;
+                self.inner.write_str(s)?;
+                match s.rfind('\n') {
+                    Some(index) => self.column = s[index + 1..].chars().count(),
+                    None => self.column += s.chars().count(),
+                }
+                self.in_word = s.ends_with(|c| c != ' ' && c != '\n');
+                return Ok(());
+            }
+        };
+        for (i, line) in s.split('\n').enumerate() {
+            if i != 0 {
+                // Trailing spaces are dropped
+                self.spaces = 0;
+                self.inner.write_char('\n')?;
+                self.column = 0;
+                self.in_word = false;
+            }
+            for (j, word) in line.split(' ').enumerate() {
+                if j != 0 {
+                    self.spaces += 1;
+                    self.in_word = false;
+                }
+                if !word.is_empty() {
+                    self.write_word(word, indent)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Width set by the `COLUMNS` environment variable, if it's set to a positive number
+#[cfg(feature = "std")]
+fn columns_env() -> Option<usize> {
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.parse().ok())
+        .filter(|&columns| columns != 0)
+}
+
+/// Usage text without the name of the command it starts with, e.g. `[OPTIONS] NAME` for
+/// `git remote add [OPTIONS] NAME` and `git remote add`. It's returned unchanged if it doesn't
+/// start with the name
+#[doc(hidden)]
+pub const fn usage_after_name(usage: &'static str, name: &'static str) -> &'static str {
+    let (usage_bytes, name_bytes) = (usage.as_bytes(), name.as_bytes());
+    if usage_bytes.len() < name_bytes.len() {
+        return usage;
+    }
+    let mut i = 0;
+    while i < name_bytes.len() {
+        if usage_bytes[i] != name_bytes[i] {
+            return usage;
+        }
+        i += 1;
+    }
+    if i < usage_bytes.len() {
+        if usage_bytes[i] != b' ' {
+            return usage;
+        }
+        i += 1;
+    }
+    let (_, rest) = usage_bytes.split_at(i);
+    match core::str::from_utf8(rest) {
+        Ok(rest) => rest,
+        Err(_) => panic!("Bug: expected valid UTF-8"),
+    }
+}
+
+/// Write the usage line of `S`, with `name` in place of [Schmargs::NAME], e.g. to show the
+/// commands a nested subcommand was reached through
+#[doc(hidden)]
+pub fn write_usage<'a, S: Schmargs<'a>>(
+    f: &mut impl fmt::Write,
+    name: &dyn fmt::Display,
+) -> fmt::Result {
+    let rest = usage_after_name(S::USAGE, S::NAME);
+    if rest.is_empty() {
+        write!(f, "Usage: {name}")
+    } else {
+        write!(f, "Usage: {name} {rest}")
+    }
+}
+
+/// Name of a subcommand along with the commands it's nested in, e.g. `git remote add`
+#[doc(hidden)]
+pub struct CommandName<'a> {
+    pub parent: &'a dyn fmt::Display,
+    pub name: &'static str,
+}
+
+impl fmt::Display for CommandName<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.parent, self.name)
+    }
+}
+
+/// Write the help text of `S` of the given kind
+#[doc(hidden)]
+pub fn write_help_kind<'a, S: Schmargs<'a>>(
+    f: impl fmt::Write,
+    min_indent: usize,
+    width: usize,
+    kind: HelpKind,
+) -> Result<usize, fmt::Error> {
+    match kind {
+        HelpKind::Short => S::write_help_with_width(f, min_indent, width),
+        HelpKind::Long => S::write_long_help_with_width(f, min_indent, width),
+        HelpKind::All => S::write_help_all_with_width(f, min_indent, width),
+    }
+}
+
+/// Width to wrap help text at when it isn't given, e.g. when it's displayed. This is the
+/// `COLUMNS` environment variable with the `std` feature, and [NO_WRAP] without it or if it isn't
+/// set
+#[doc(hidden)]
+pub fn default_help_width() -> usize {
+    #[cfg(feature = "std")]
+    let width = columns_env().unwrap_or(NO_WRAP);
+    #[cfg(not(feature = "std"))]
+    let width = NO_WRAP;
+    width
+}
+
+/// Write the description at the top of the help text, if any
+#[doc(hidden)]
+pub fn write_description<W: fmt::Write>(
+    f: &mut HelpWriter<W>,
+    description: &str,
+    long_description: &str,
+    long: bool,
+) -> fmt::Result {
+    let description = if long { long_description } else { description };
+    if description.is_empty() {
+        return Ok(());
+    }
+    f.wrap(Some(0))?;
+    writeln!(f, "{description}")?;
+    writeln!(f)?;
+    f.wrap(None)
+}
+
+/// Width of the left portion of a help entry
+#[doc(hidden)]
+pub fn help_entry_width(before: &str, prefix: FlagPrefix, after: &str) -> usize {
+    before.len() + prefix.len() + after.len()
+}
+
+/// Write a help entry, e.g. "-s, --db-host <HOST>  Host to connect to"
+///
+/// The left portion is `before`, `prefix`, and `after`, and is padded to `min_indent`. The
+/// description, and whatever is written after it, is wrapped and aligned with its first line
+#[doc(hidden)]
+pub fn write_help_entry<W: fmt::Write>(
+    f: &mut HelpWriter<W>,
+    before: &str,
+    prefix: FlagPrefix,
+    after: &str,
+    min_indent: usize,
+    description: &str,
+) -> fmt::Result {
+    f.wrap(None)?;
+    write!(f, "\n{before}{prefix}{after}")?;
+    if description.is_empty() {
+        return f.wrap(Some(min_indent));
+    }
+    for _ in 0..min_indent.saturating_sub(help_entry_width(before, prefix, after)) {
+        write!(f, " ")?;
+    }
+    f.wrap(Some(min_indent))?;
+    // Lines after the first line of a long description are aligned with it
+    for (i, line) in description.split('\n').enumerate() {
+        if i != 0 {
+            writeln!(f)?;
+            if !line.is_empty() {
+                for _ in 0..min_indent {
+                    write!(f, " ")?;
+                }
+            }
+        }
+        write!(f, "{line}")?;
+    }
+    Ok(())
+}
+
+/// Capacity of the usage text of a [Schmargs] type with flattened fields
+#[doc(hidden)]
+pub const USAGE_CAPACITY: usize = 256;
+
+/// A fixed-capacity string that can be built in a const context
+#[doc(hidden)]
+pub struct ConstStr<const N: usize> {
+    buffer: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> ConstStr<N> {
+    /// Construct empty string
+    pub const fn new() -> Self {
+        Self {
+            buffer: [0; N],
+            len: 0,
+        }
+    }
+
+    /// Append `string`
+    pub const fn push(mut self, string: &str) -> Self {
+        let bytes = string.as_bytes();
+        assert!(self.len + bytes.len() <= N, "String too long");
+        let mut i = 0;
+        while i < bytes.len() {
+            self.buffer[self.len] = bytes[i];
+            self.len += 1;
+            i += 1;
+        }
+        self
+    }
+
+    /// Append `string` if `condition` is true
+    pub const fn push_if(self, condition: bool, string: &str) -> Self {
+        if condition {
+            self.push(string)
+        } else {
+            self
+        }
+    }
+
+    /// Append the usage of each required option, e.g. " --name <NAME>"
+    pub const fn push_required(mut self, prefix: &str, required: &RequiredOptions) -> Self {
+        let mut i = 0;
+        while i < required.options.len() {
+            let (dashes, rest) = required.options[i];
+            // Only long flags are prefixed
+            self = self
+                .push(" ")
+                .push(dashes)
+                .push_if(dashes.len() == 2, prefix)
+                .push(rest);
+            i += 1;
+        }
+        let mut i = 0;
+        while i < required.flattened.len() {
+            let (inner, flattened) = required.flattened[i];
+            let prefix = ConstStr::<N>::new().push(prefix).push(inner);
+            self = self.push_required(prefix.as_str(), flattened);
+            i += 1;
+        }
+        self
+    }
+
+    /// View as str
+    pub const fn as_str(&self) -> &str {
+        let (bytes, _) = self.buffer.split_at(self.len);
+        match core::str::from_utf8(bytes) {
+            Ok(string) => string,
+            Err(_) => panic!("Bug: expected valid UTF-8"),
+        }
+    }
+}
+
+impl<const N: usize> Default for ConstStr<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Write the possible values of an argument, for the help text
+#[doc(hidden)]
+pub fn write_possible_values(
+    mut f: impl fmt::Write,
+    values: &'static [&'static str],
+) -> fmt::Result {
+    if !values.is_empty() {
+        write!(f, " [possible values: {}]", ValueList(values))?;
+    }
+    Ok(())
+}
+
+/// Write the visible aliases of a flag, for the help text. `longs` don't include the leading
+/// "--"
+#[doc(hidden)]
+pub fn write_aliases(
+    mut f: impl fmt::Write,
+    prefix: FlagPrefix,
+    shorts: &[char],
+    longs: &[&str],
+) -> fmt::Result {
+    if shorts.is_empty() && longs.is_empty() {
+        return Ok(());
+    }
+    write!(f, " [aliases: ")?;
+    let mut first = true;
+    for short in shorts {
+        if !first {
+            write!(f, ", ")?;
+        }
+        write!(f, "-{short}")?;
+        first = false;
+    }
+    for long in longs {
+        if !first {
+            write!(f, ", ")?;
+        }
+        write!(f, "--{prefix}{long}")?;
+        first = false;
+    }
+    write!(f, "]")
+}
+
+/// Longest candidate, in chars, that can be suggested. This is the size of the scratch buffer used
+/// to compute edit distances
+#[doc(hidden)]
+pub const SUGGESTION_CAPACITY: usize = 32;
+
+// Finds the candidate closest to a mistyped value, by the edit distance between them.
+// Candidates that are too far off to be a typo aren't suggested
+struct Suggestion<'g> {
+    got: &'g str,
+    closest: Option<(usize, &'static str)>,
+}
+
+impl<'g> Suggestion<'g> {
+    fn new(got: &'g str) -> Self {
+        Self { got, closest: None }
+    }
+
+    // Earlier candidates win ties
+    fn add(&mut self, candidate: &'static str) {
+        let Some(distance) = edit_distance(self.got, candidate) else {
+            return;
+        };
+        let len = self.got.chars().count().max(candidate.chars().count());
+        if distance > len / 3 {
+            return;
+        }
+        if self.closest.is_none_or(|(closest, _)| distance < closest) {
+            self.closest = Some((distance, candidate));
+        }
+    }
+
+    fn add_longs(&mut self, args: &'static [ArgSpec]) {
+        for arg in args {
+            match arg.kind {
+                ArgKind::Flatten { prefix: "", args } => self.add_longs(args),
+                ArgKind::Flatten { .. } => {}
+                _ if arg.hidden => {}
+                _ => {
+                    for long in arg.long.iter().chain(arg.visible_aliases) {
+                        self.add(long);
+                    }
+                    if let Some(long) = arg.negated_long {
+                        self.add(long);
+                    }
+                }
+            }
+        }
+    }
+
+    fn finish(self) -> Option<&'static str> {
+        self.closest.map(|(_, candidate)| candidate)
+    }
+}
+
+/// Closest long flag, negated long flag, or visible alias of `args` to `long`, if any is close
+/// enough. Both are without the leading "--". Flags of flattened fields with a prefix aren't
+/// suggested, since they don't exist as a `'static` string, see [suggest_prefixed_long]
+#[doc(hidden)]
+pub fn suggest_long(long: &str, args: &'static [ArgSpec]) -> Option<&'static str> {
+    let mut suggestion = Suggestion::new(long);
+    suggestion.add_longs(args);
+    suggestion.finish()
+}
+
+/// Like [suggest_long], but also suggest the flags of flattened fields with a prefix, which are
+/// joined with their prefix in `prefixed`
+#[doc(hidden)]
+pub fn suggest_prefixed_long(
+    long: &str,
+    args: &'static [ArgSpec],
+    prefixed: &'static PrefixedLongs,
+) -> Option<&'static str> {
+    let mut suggestion = Suggestion::new(long);
+    suggestion.add_longs(args);
+    for long in &prefixed.longs[..prefixed.len] {
+        suggestion.add(long.as_str());
+    }
+    suggestion.finish()
+}
+
+/// Most flags of flattened fields with a prefix that [PrefixedLongs] holds
+#[doc(hidden)]
+pub const PREFIXED_LONGS_CAPACITY: usize = 16;
+
+/// Long flags, negated long flags, and visible aliases of flattened fields with a prefix, joined
+/// with their prefix at compile time, e.g. `db-host`. Flags past [PREFIXED_LONGS_CAPACITY], and
+/// those longer than [SUGGESTION_CAPACITY], which couldn't be suggested anyway, are left out
+#[doc(hidden)]
+pub struct PrefixedLongs {
+    longs: [ConstStr<SUGGESTION_CAPACITY>; PREFIXED_LONGS_CAPACITY],
+    len: usize,
+}
+
+impl PrefixedLongs {
+    /// Collect the prefixed flags of `args`
+    pub const fn new(args: &'static [ArgSpec]) -> Self {
+        let longs = Self {
+            longs: [const { ConstStr::new() }; PREFIXED_LONGS_CAPACITY],
+            len: 0,
+        };
+        longs.add(ConstStr::new(), args)
+    }
+
+    const fn add(
+        mut self,
+        prefix: ConstStr<SUGGESTION_CAPACITY>,
+        args: &'static [ArgSpec],
+    ) -> Self {
+        let mut i = 0;
+        while i < args.len() {
+            let arg = &args[i];
+            i += 1;
+            if let ArgKind::Flatten {
+                prefix: inner,
+                args,
+            } = arg.kind
+            {
+                if prefix.len + inner.len() <= SUGGESTION_CAPACITY {
+                    let prefix = ConstStr::new().push(prefix.as_str()).push(inner);
+                    self = self.add(prefix, args);
+                }
+                continue;
+            }
+            // Flags without a prefix are suggested as they are
+            if arg.hidden || prefix.len == 0 {
+                continue;
+            }
+            if let Some(long) = arg.long {
+                self = self.push(&prefix, long);
+            }
+            let mut j = 0;
+            while j < arg.visible_aliases.len() {
+                self = self.push(&prefix, arg.visible_aliases[j]);
+                j += 1;
+            }
+            if let Some(long) = arg.negated_long {
+                self = self.push(&prefix, long);
+            }
+        }
+        self
+    }
+
+    const fn push(mut self, prefix: &ConstStr<SUGGESTION_CAPACITY>, long: &str) -> Self {
+        if self.len < PREFIXED_LONGS_CAPACITY && prefix.len + long.len() <= SUGGESTION_CAPACITY {
+            self.longs[self.len] = ConstStr::new().push(prefix.as_str()).push(long);
+            self.len += 1;
+        }
+        self
+    }
+}
+
+/// Closest name of `commands` to `got`, if any is close enough
+#[doc(hidden)]
+pub fn suggest_command(got: &str, commands: &'static [CommandSpec]) -> Option<&'static str> {
+    let mut suggestion = Suggestion::new(got);
+    for command in commands {
+        suggestion.add(command.name);
+    }
+    suggestion.finish()
+}
+
+/// Closest value of `values` to `got`, if any is close enough
+#[doc(hidden)]
+pub fn suggest_value(got: &str, values: &'static [&'static str]) -> Option<&'static str> {
+    let mut suggestion = Suggestion::new(got);
+    for value in values {
+        suggestion.add(value);
+    }
+    suggestion.finish()
+}
+
+// Edit distance between `a` and `b`, where swapping two adjacent chars counts as one edit (the
+// optimal string alignment distance). Only the last three rows of the table are kept. Returns
+// `None` if `b` is longer than `SUGGESTION_CAPACITY`
+fn edit_distance(a: &str, b: &str) -> Option<usize> {
+    let b_len = b.chars().count();
+    if b_len > SUGGESTION_CAPACITY {
+        return None;
+    }
+    // Rows for the part of `a` before the previous char, up to the previous char, and up to the
+    // current char. `row[j]` is the distance to the first `j` chars of `b`
+    let mut rows = [[0; SUGGESTION_CAPACITY + 1]; 3];
+    for (j, cell) in rows[1].iter_mut().enumerate().take(b_len + 1) {
+        *cell = j;
+    }
+    let mut previous_a = None;
+    for (i, a_char) in a.chars().enumerate() {
+        let [before, previous, current] = &mut rows;
+        current[0] = i + 1;
+        let mut previous_b = None;
+        for (j, b_char) in b.chars().enumerate() {
+            let mut distance = (previous[j] + usize::from(a_char != b_char))
+                .min(previous[j + 1] + 1)
+                .min(current[j] + 1);
+            if previous_a == Some(b_char) && previous_b == Some(a_char) {
+                distance = distance.min(before[j - 1] + 1);
+            }
+            current[j + 1] = distance;
+            previous_b = Some(b_char);
+        }
+        rows.rotate_left(1);
+        previous_a = Some(a_char);
+    }
+    Some(rows[1][b_len])
+}
+
+/// An argument type that can be constructed from the value of an environment variable
+#[cfg(feature = "std")]
+#[doc(hidden)]
+pub trait EnvValue: Sized {
+    /// Construct from the value of an environment variable
+    fn from_env(value: String) -> Self;
+}
+
+#[cfg(feature = "std")]
+impl EnvValue for String {
+    fn from_env(value: String) -> Self {
+        value
+    }
+}
+
+// Environment variables are only read once per argument, and would outlive the arguments anyway
+#[cfg(feature = "std")]
+impl EnvValue for &str {
+    fn from_env(value: String) -> Self {
+        String::leak(value)
+    }
+}
+
+/// Read an environment variable. Variables that aren't set or aren't valid unicode are ignored
+#[cfg(feature = "std")]
+#[doc(hidden)]
+pub fn env_var<T: EnvValue>(name: &str) -> Option<T> {
+    std::env::var(name).ok().map(T::from_env)
+}
+
 #[derive(Debug, PartialEq, Eq)]
 #[doc(hidden)]
 pub enum DumbArgument<T> {
@@ -18,7 +1176,7 @@ impl<T> DumbArgument<T> {
 /// An iterator that parses out short flags (`-s`), long flags(`--long`), and values out of an
 /// iterator of arguments
 #[doc(hidden)]
-pub struct DumbIterator<T: AsRef<str>, InputIterator: Iterator<Item = T>> {
+pub struct DumbIterator<T, InputIterator: Iterator<Item = T>> {
     hit_double_dash: bool,
     args: InputIterator,
 }
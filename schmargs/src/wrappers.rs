@@ -1,5 +1,12 @@
 // Schmargs wrappers that provide additional functionality, like `--version` and `--help` options
-use crate::{Schmargs, SchmargsError};
+use crate::{
+    spec::{ArgKind, ArgSpec, CommandSpec},
+    utils::{
+        default_help_width, suggest_long, HelpKind, HelpWriter, NoHelpLong, NoHelpShort,
+        NoVersionLong, NoVersionShort, NO_WRAP,
+    },
+    Schmargs, SchmargsError,
+};
 use core::fmt;
 
 /// A generic wrapper
@@ -9,7 +16,21 @@ pub trait Wrapper: Sized {
     const DESCRIPTION: &'static str;
     type SchmargsType;
     fn parsed(inner: Self::SchmargsType) -> Self;
-    fn special() -> Self;
+    /// Returned when the wrapper's flag is passed. `request` describes the help text that was asked
+    /// for, and is ignored by wrappers that don't print help
+    fn special(request: HelpRequest) -> Self;
+    /// Returned when the flag set by `show_hidden_with` is passed. If this is `None`, the flag is
+    /// left to outer wrappers
+    fn show_hidden(request: HelpRequest) -> Option<Self> {
+        let _ = request;
+        None
+    }
+    /// Help text asked for by this wrapper, without looking at the wrapped arguments
+    fn own_help_request(&self) -> Option<HelpRequest> {
+        None
+    }
+    /// Wrapped arguments, if they were parsed
+    fn inner(&self) -> Option<&Self::SchmargsType>;
 }
 
 impl<'a, W: Wrapper> Schmargs<'a> for W
@@ -23,38 +44,130 @@ where
     const VERSION: &'static str = W::SchmargsType::VERSION;
     const USAGE: &'static str = W::SchmargsType::USAGE;
     const DESCRIPTION: &'static str = W::SchmargsType::DESCRIPTION;
+    const LONG_DESCRIPTION: &'static str = W::SchmargsType::LONG_DESCRIPTION;
+    const SHOW_HIDDEN_WITH: Option<&'static str> = W::SchmargsType::SHOW_HIDDEN_WITH;
+    const ARGS: &'static [ArgSpec] = &[
+        ArgSpec {
+            kind: ArgKind::Flatten {
+                prefix: "",
+                args: W::SchmargsType::ARGS,
+            },
+            ..ArgSpec::DEFAULT
+        },
+        ArgSpec {
+            kind: ArgKind::Flag,
+            name: strip_dashes(W::LONG_OPTION),
+            short: Some(W::SHORT_OPTION),
+            long: Some(strip_dashes(W::LONG_OPTION)),
+            description: W::DESCRIPTION,
+            long_description: W::DESCRIPTION,
+            optional: true,
+            ..ArgSpec::DEFAULT
+        },
+    ];
+    const SUBCOMMANDS: &'static [CommandSpec] = W::SchmargsType::SUBCOMMANDS;
 
     fn write_help_with_min_indent(
-        mut f: impl fmt::Write,
+        f: impl fmt::Write,
         min_indent: usize,
     ) -> Result<usize, fmt::Error> {
-        let prefix_len = "-h, ".len() + W::LONG_OPTION.len();
-        let min_indent = core::cmp::max(min_indent, prefix_len + 1);
-        let min_indent = core::cmp::max(
-            min_indent,
-            W::SchmargsType::write_help_with_min_indent(&mut f, min_indent)?,
-        );
-        writeln!(f)?;
-        write!(f, "-{}, {}", W::SHORT_OPTION, W::LONG_OPTION)?;
-        for _ in 0..(min_indent - prefix_len) {
-            write!(f, " ")?;
-        }
-        write!(f, "{}", W::DESCRIPTION)?;
-        Ok(min_indent)
+        Self::write_help_with_width(f, min_indent, NO_WRAP)
+    }
+
+    fn write_long_help_with_min_indent(
+        f: impl fmt::Write,
+        min_indent: usize,
+    ) -> Result<usize, fmt::Error> {
+        Self::write_long_help_with_width(f, min_indent, NO_WRAP)
+    }
+
+    fn write_help_all_with_min_indent(
+        f: impl fmt::Write,
+        min_indent: usize,
+    ) -> Result<usize, fmt::Error> {
+        Self::write_help_all_with_width(f, min_indent, NO_WRAP)
+    }
+
+    fn write_help_with_width(
+        f: impl fmt::Write,
+        min_indent: usize,
+        width: usize,
+    ) -> Result<usize, fmt::Error> {
+        Ok(Self::write_subcommand_help(&[], &Self::NAME, f, min_indent, width, HelpKind::Short)?.0)
+    }
+
+    fn write_long_help_with_width(
+        f: impl fmt::Write,
+        min_indent: usize,
+        width: usize,
+    ) -> Result<usize, fmt::Error> {
+        Ok(Self::write_subcommand_help(&[], &Self::NAME, f, min_indent, width, HelpKind::Long)?.0)
+    }
+
+    fn write_help_all_with_width(
+        f: impl fmt::Write,
+        min_indent: usize,
+        width: usize,
+    ) -> Result<usize, fmt::Error> {
+        Ok(Self::write_subcommand_help(&[], &Self::NAME, f, min_indent, width, HelpKind::All)?.0)
+    }
+
+    fn write_subcommand_help(
+        path: &[u16],
+        name: &dyn fmt::Display,
+        mut f: impl fmt::Write,
+        min_indent: usize,
+        width: usize,
+        kind: HelpKind,
+    ) -> Result<(usize, bool), fmt::Error> {
+        let min_indent = core::cmp::max(min_indent, wrapper_help_width::<W>());
+        let (indent, options) =
+            W::SchmargsType::write_subcommand_help(path, name, &mut f, min_indent, width, kind)?;
+        let min_indent = core::cmp::max(min_indent, indent);
+        Ok((
+            write_wrapper_help::<W>(f, min_indent, width, !options)?,
+            true,
+        ))
+    }
+
+    fn help_request(&self) -> Option<HelpRequest> {
+        self.own_help_request()
+            .or_else(|| self.inner().and_then(|inner| inner.help_request()))
     }
 
     fn parse(args: impl Iterator<Item = Self::Item>) -> Result<Self, SchmargsError<Self::Item>> {
+        // Follow the subcommands, so help is shown for the one it was asked for in
+        let mut commands = CommandPath::new(Self::SUBCOMMANDS);
+        let args = args.inspect(|arg| commands.visit(arg.as_ref()));
         match W::SchmargsType::parse(args) {
             Ok(inner) => Ok(W::parsed(inner)),
             Err(inner) => {
                 match inner {
-                    SchmargsError::NoSuchShortFlag(val) => {
-                        if val == W::SHORT_OPTION {
-                            return Ok(W::special());
+                    SchmargsError::NoSuchShortFlag(val) if val == W::SHORT_OPTION => {
+                        return Ok(W::special(commands.request(false)));
+                    }
+                    SchmargsError::NoSuchLongFlag { got, .. } if got.as_ref() == W::LONG_OPTION => {
+                        return Ok(W::special(commands.request(false)));
+                    }
+                    SchmargsError::NoSuchLongFlag { ref got, .. }
+                        if Some(got.as_ref()) == Self::SHOW_HIDDEN_WITH =>
+                    {
+                        if let Some(show_hidden) = W::show_hidden(commands.request(true)) {
+                            return Ok(show_hidden);
                         }
                     }
-                    SchmargsError::NoSuchLongFlag(val) if val.as_ref() == W::LONG_OPTION => {
-                        return Ok(W::special());
+                    // Fall back on suggesting the wrapper's flag
+                    SchmargsError::NoSuchLongFlag {
+                        got,
+                        suggestion: None,
+                    } => {
+                        return Err(SchmargsError::NoSuchLongFlag {
+                            suggestion: got
+                                .as_ref()
+                                .strip_prefix("--")
+                                .and_then(|long| suggest_long(long, Self::ARGS)),
+                            got,
+                        });
                     }
                     _ => {}
                 }
@@ -64,6 +177,116 @@ where
     }
 }
 
+// Long flag without the leading "--"
+const fn strip_dashes(long: &'static str) -> &'static str {
+    let (_, name) = long.as_bytes().split_at(2);
+    match core::str::from_utf8(name) {
+        Ok(name) => name,
+        Err(_) => panic!("Bug: expected valid UTF-8"),
+    }
+}
+
+// Minimum indent needed to fit the help entry of a wrapper's option
+fn wrapper_help_width<W: Wrapper>() -> usize {
+    "-h, ".len() + W::LONG_OPTION.len() + 1
+}
+
+// Write the help entry of a wrapper's option, under a new "Options:" heading if `heading` is set
+fn write_wrapper_help<W: Wrapper>(
+    f: impl fmt::Write,
+    min_indent: usize,
+    width: usize,
+    heading: bool,
+) -> Result<usize, fmt::Error> {
+    let mut f = HelpWriter::new(f, width);
+    let prefix_len = "-h, ".len() + W::LONG_OPTION.len();
+    if heading {
+        writeln!(f, "\n")?;
+        write!(f, "Options:")?;
+    }
+    writeln!(f)?;
+    write!(f, "-{}, {}", W::SHORT_OPTION, W::LONG_OPTION)?;
+    for _ in 0..(min_indent - prefix_len) {
+        write!(f, " ")?;
+    }
+    f.wrap(Some(min_indent))?;
+    write!(f, "{}", W::DESCRIPTION)?;
+    Ok(min_indent)
+}
+
+// Write the help text asked for by `args`, or the help text if it didn't ask for any
+fn write_requested_help<'a, S: Schmargs<'a>>(args: &S, f: impl fmt::Write) -> fmt::Result {
+    let request = args.help_request().unwrap_or_default();
+    let kind = if request.shows_hidden() {
+        HelpKind::All
+    } else {
+        HelpKind::Short
+    };
+    S::write_subcommand_help(request.path(), &S::NAME, f, 0, default_help_width(), kind)?;
+    Ok(())
+}
+
+// Subcommands nested deeper than this show the help text of their ancestor at this depth
+const MAX_DEPTH: usize = 8;
+
+/// Help text asked for with [ArgsWithHelp]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Ord, PartialOrd, Hash)]
+pub struct HelpRequest {
+    show_hidden: bool,
+    // Index into `SUBCOMMANDS` of each subcommand the help was asked for in
+    path: [u16; MAX_DEPTH],
+    depth: u8,
+}
+
+impl HelpRequest {
+    /// Whether the help text should include arguments with `#[arg(hide)]`. This is true when the
+    /// flag set by `#[schmargs(show_hidden_with = ...)]` was passed, e.g. '--help-all'
+    pub const fn shows_hidden(&self) -> bool {
+        self.show_hidden
+    }
+
+    // Subcommand the help was asked for in, see `Schmargs::write_subcommand_help`
+    fn path(&self) -> &[u16] {
+        &self.path[..usize::from(self.depth)]
+    }
+}
+
+// Subcommands named by the arguments so far, e.g. `remote add` in `git remote add --help`
+struct CommandPath {
+    // Subcommands the next argument can name. Empty once an argument doesn't name one
+    commands: &'static [CommandSpec],
+    request: HelpRequest,
+}
+
+impl CommandPath {
+    fn new(commands: &'static [CommandSpec]) -> Self {
+        Self {
+            commands,
+            request: HelpRequest::default(),
+        }
+    }
+
+    fn visit(&mut self, arg: &str) {
+        let depth = usize::from(self.request.depth);
+        let index = self.commands.iter().position(|command| command.name == arg);
+        match index.map(u16::try_from) {
+            Some(Ok(index)) if depth < MAX_DEPTH => {
+                self.request.path[depth] = index;
+                self.request.depth += 1;
+                self.commands = self.commands[usize::from(index)].subcommands;
+            }
+            _ => self.commands = &[],
+        }
+    }
+
+    fn request(&self, show_hidden: bool) -> HelpRequest {
+        HelpRequest {
+            show_hidden,
+            ..self.request
+        }
+    }
+}
+
 /// A wrapper that provides `--help` functionality
 ///
 /// # Example
@@ -83,16 +306,17 @@ where
 /// type Args = ArgsWithHelp<BareArgs>;
 ///
 /// let args = Args::parse("--help".split_whitespace()).unwrap();
-/// match args {
-///     Args::Args(args) => {
-///         if args.meow {
+/// match &args {
+///     Args::Args(bare) => {
+///         if bare.meow {
 ///             println!("Meow!");
 ///         } else {
 ///             println!("Bark!");
 ///         }
 ///     }
-///     Args::Help => {
-///         println!("{}", Args::Help);
+///     // Print help, which includes hidden arguments if they were asked for
+///     Args::Help(_) => {
+///         println!("{args}");
 ///     }
 /// }
 /// ```
@@ -114,27 +338,30 @@ where
 /// type Args<'a> = ArgsWithHelp<BareArgs<'a>>;
 ///
 /// let args = ArgsWithHelp::parse("--help".split_whitespace()).unwrap();
-/// match args {
-///     Args::Args(args) => {
-///         println!("Hello, {}!", args.person);
-///         if args.kick_shins {
+/// match &args {
+///     Args::Args(bare) => {
+///         println!("Hello, {}!", bare.person);
+///         if bare.kick_shins {
 ///             println!("Now I'm gonna kick your shins!");
 ///         }
 ///     }
-///     Args::Help => {
-///         println!("{}", Args::Help);
+///     // Print help, which includes hidden arguments if they were asked for
+///     Args::Help(_) => {
+///         println!("{args}");
 ///     }
 /// }
 /// ```
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Ord, PartialOrd)]
 pub enum ArgsWithHelp<S> {
-    /// User passed the '-h' or '--help' flag
-    Help,
+    /// User passed the '-h' or '--help' flag, or the flag set by
+    /// `#[schmargs(show_hidden_with = ...)]`, e.g. '--help-all'
+    Help(HelpRequest),
     /// Parsed arguments
     Args(S),
 }
 
-impl<S> Wrapper for ArgsWithHelp<S> {
+// An argument with the same flag would shadow the wrapper's
+impl<S: NoHelpShort + NoHelpLong> Wrapper for ArgsWithHelp<S> {
     const SHORT_OPTION: char = 'h';
     const LONG_OPTION: &'static str = "--help";
     const DESCRIPTION: &'static str = "Print help";
@@ -144,21 +371,42 @@ impl<S> Wrapper for ArgsWithHelp<S> {
         Self::Args(inner)
     }
 
-    fn special() -> Self {
-        Self::Help
+    fn special(request: HelpRequest) -> Self {
+        Self::Help(request)
+    }
+
+    fn show_hidden(request: HelpRequest) -> Option<Self> {
+        Some(Self::Help(request))
+    }
+
+    fn own_help_request(&self) -> Option<HelpRequest> {
+        match self {
+            Self::Help(request) => Some(*request),
+            Self::Args(_) => None,
+        }
+    }
+
+    fn inner(&self) -> Option<&S> {
+        match self {
+            Self::Help(_) => None,
+            Self::Args(inner) => Some(inner),
+        }
     }
 }
 
-impl<'a, S: Schmargs<'a>> fmt::Display for ArgsWithHelp<S>
+impl<'a, S: Schmargs<'a> + NoHelpShort + NoHelpLong> fmt::Display for ArgsWithHelp<S>
 where
     <S as Schmargs<'a>>::Item: AsRef<str>,
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        Self::write_help_with_min_indent(f, 0)?;
-        Ok(())
+        write_requested_help(self, f)
     }
 }
 
+// Wrappers can be nested, as long as the flags they add don't conflict
+impl<S: NoVersionShort> NoVersionShort for ArgsWithHelp<S> {}
+impl<S: NoVersionLong> NoVersionLong for ArgsWithHelp<S> {}
+
 /// A wrapper that provides `--version` functionality
 ///
 /// This can be used with [ArgsWithHelp] or by itself
@@ -178,9 +426,9 @@ where
 /// type Args = ArgsWithHelp<ArgsWithVersion<BareArgs>>;
 ///
 /// let args = Args::parse("--version".split_whitespace()).unwrap();
-/// match args {
-///     Args::Args(ArgsWithVersion::Args(args)) => {
-///         if args.meow {
+/// match &args {
+///     Args::Args(ArgsWithVersion::Args(bare)) => {
+///         if bare.meow {
 ///             println!("Meow!");
 ///         } else {
 ///             println!("Bark!");
@@ -190,9 +438,9 @@ where
 ///     Args::Args(ArgsWithVersion::Version) => {
 ///         println!("{}", Args::VERSION);
 ///     }
-///     // Print help
-///     Args::Help => {
-///         println!("{}", Args::Help);
+///     // Print help, which includes hidden arguments if they were asked for
+///     Args::Help(_) => {
+///         println!("{args}");
 ///     }
 /// }
 /// ```
@@ -204,7 +452,7 @@ pub enum ArgsWithVersion<S> {
     Args(S),
 }
 
-impl<S> Wrapper for ArgsWithVersion<S> {
+impl<S: NoVersionShort + NoVersionLong> Wrapper for ArgsWithVersion<S> {
     const SHORT_OPTION: char = 'v';
     const LONG_OPTION: &'static str = "--version";
     const DESCRIPTION: &'static str = "Print version";
@@ -214,17 +462,26 @@ impl<S> Wrapper for ArgsWithVersion<S> {
         Self::Args(inner)
     }
 
-    fn special() -> Self {
+    fn special(_request: HelpRequest) -> Self {
         Self::Version
     }
+
+    fn inner(&self) -> Option<&S> {
+        match self {
+            Self::Version => None,
+            Self::Args(inner) => Some(inner),
+        }
+    }
 }
 
-impl<'a, S: Schmargs<'a>> fmt::Display for ArgsWithVersion<S>
+impl<'a, S: Schmargs<'a> + NoVersionShort + NoVersionLong> fmt::Display for ArgsWithVersion<S>
 where
     <S as Schmargs<'a>>::Item: AsRef<str>,
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        Self::write_help_with_min_indent(f, 0)?;
-        Ok(())
+        write_requested_help(self, f)
     }
 }
+
+impl<S: NoHelpShort> NoHelpShort for ArgsWithVersion<S> {}
+impl<S: NoHelpLong> NoHelpLong for ArgsWithVersion<S> {}
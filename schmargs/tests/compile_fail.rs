@@ -0,0 +1,7 @@
+//! Make sure mistakes in attributes are reported as compile errors that point at the mistake
+#[test]
+fn compile_fail() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/compile_pass/*.rs");
+    t.compile_fail("tests/compile_fail/*.rs");
+}
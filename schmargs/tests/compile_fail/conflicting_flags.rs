@@ -0,0 +1,17 @@
+use schmargs::Schmargs;
+
+/// Flags that are used twice
+#[derive(Schmargs)]
+struct Args {
+    /// Be verbose
+    #[arg(short, long)]
+    verbose: bool,
+    /// Print the version
+    #[arg(short, long = "verbose")]
+    version: bool,
+    /// Be quiet
+    #[arg(long, alias = "verbose")]
+    quiet: bool,
+}
+
+fn main() {}
@@ -0,0 +1,24 @@
+use schmargs::Schmargs;
+
+/// Options shared by all commands
+#[derive(Schmargs)]
+struct Common {
+    /// Be verbose
+    #[arg(short, long)]
+    verbose: bool,
+}
+
+/// A flattened flag that's used twice
+#[derive(Schmargs)]
+struct Args {
+    /// Shared options
+    #[arg(flatten)]
+    common: Common,
+    /// Print the version
+    #[arg(short = 'v', long)]
+    version: bool,
+}
+
+fn main() {
+    let _ = Args::parse(core::iter::empty());
+}
@@ -0,0 +1,30 @@
+use schmargs::{ArgsWithHelp, ArgsWithVersion, Schmargs};
+
+/// A flag that's also used by the help wrapper
+#[derive(Schmargs)]
+struct Args {
+    /// Be helpful
+    #[arg(long)]
+    help: bool,
+}
+
+/// Options shared by all commands
+#[derive(Schmargs)]
+struct Common {
+    /// Be verbose
+    #[arg(short, long)]
+    verbose: bool,
+}
+
+/// A flattened flag that's also used by the version wrapper
+#[derive(Schmargs)]
+struct Flattened {
+    /// Shared options
+    #[arg(flatten)]
+    common: Common,
+}
+
+fn main() {
+    let _ = ArgsWithHelp::<Args>::parse(core::iter::empty());
+    let _ = ArgsWithVersion::<Flattened>::parse(core::iter::empty());
+}
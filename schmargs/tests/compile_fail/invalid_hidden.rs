@@ -0,0 +1,40 @@
+use schmargs::Schmargs;
+
+/// Shared options
+#[derive(Schmargs)]
+struct Common {
+    /// Be verbose
+    #[arg(short, long)]
+    verbose: bool,
+}
+
+/// Hiding a flattened field
+#[derive(Schmargs)]
+struct Flattened {
+    #[arg(flatten, hide)]
+    common: Common,
+}
+
+/// Not a long flag
+#[derive(Schmargs)]
+#[schmargs(show_hidden_with = "-H")]
+struct Short {}
+
+/// A flag that's already used
+#[derive(Schmargs)]
+#[schmargs(show_hidden_with = "--all")]
+struct Taken {
+    /// Show all files
+    #[arg(short, long)]
+    all: bool,
+}
+
+/// Enums don't have hidden arguments
+#[derive(Schmargs)]
+#[schmargs(show_hidden_with = "--help-all")]
+enum Commands {
+    /// Do nothing
+    Nothing {},
+}
+
+fn main() {}
@@ -0,0 +1,29 @@
+use schmargs::Schmargs;
+
+/// Relations and groups that name arguments that don't exist
+#[derive(Schmargs)]
+#[schmargs(group(name = "transport", args = [tcp, udp]))]
+struct Args {
+    /// Use TCP
+    #[arg(long, requires = prot)]
+    tcp: bool,
+    /// Port
+    #[arg(long, conflicts_with = [tcp, nope])]
+    port: Option<u16>,
+}
+
+/// Arguments that can be left out, but have no value to fall back on
+#[derive(Schmargs)]
+struct Input<'a> {
+    /// Read from stdin
+    #[arg(long)]
+    stdin: bool,
+    /// File to read
+    #[arg(required_unless_present = [stdin])]
+    file: &'a str,
+    /// Name of the input
+    #[arg(long, required_unless_present = [stdin])]
+    name: &'a str,
+}
+
+fn main() {}
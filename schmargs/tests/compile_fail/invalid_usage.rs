@@ -0,0 +1,38 @@
+use schmargs::{Schmargs, SchmargsValue};
+
+/// Attributes used where they don't apply
+#[derive(Schmargs)]
+struct Args {
+    /// Prefix without flatten
+    #[arg(long, prefix = "db-")]
+    host: Option<u16>,
+    /// Unknown action
+    #[arg(short, action = append)]
+    verbose: u8,
+    /// Environment variable on a flag
+    #[arg(short, env)]
+    force: bool,
+}
+
+/// Value hints that don't apply
+#[derive(Schmargs)]
+struct Hints {
+    /// Value hint on a flag
+    #[arg(short, value_hint = dir)]
+    force: bool,
+    /// Unknown value hint
+    #[arg(long, value_hint = folder)]
+    output: Option<u8>,
+}
+
+/// Flags in a tuple struct
+#[derive(Schmargs)]
+struct Tuple(#[arg(short)] bool, u32);
+
+#[derive(SchmargsValue)]
+enum Format {
+    Json,
+    Yaml(u8),
+}
+
+fn main() {}
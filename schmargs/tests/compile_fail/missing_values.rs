@@ -0,0 +1,12 @@
+use schmargs::Schmargs;
+
+/// Keys without values
+#[derive(Schmargs)]
+#[schmargs(name =)]
+struct Args {
+    /// Number of jobs
+    #[arg(short, alias)]
+    jobs: u8,
+}
+
+fn main() {}
@@ -0,0 +1,21 @@
+use schmargs::Schmargs;
+
+/// Required positional arguments after optional and variadic ones
+#[derive(Schmargs)]
+struct Args<'a> {
+    /// Input file
+    input: Option<&'a str>,
+    /// Output file
+    output: &'a str,
+}
+
+/// A required positional argument after a variadic one
+#[derive(Schmargs)]
+struct Copy<'a> {
+    /// Files to copy
+    sources: Vec<&'a str>,
+    /// Where to copy them
+    destination: &'a str,
+}
+
+fn main() {}
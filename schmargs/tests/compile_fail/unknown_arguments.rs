@@ -0,0 +1,14 @@
+use schmargs::Schmargs;
+
+/// Errors in several fields are reported at once
+#[derive(Schmargs)]
+struct Args {
+    /// Be verbose
+    #[arg(shortt)]
+    verbose: bool,
+    /// Be quiet
+    #[arg(short, lnog)]
+    quiet: bool,
+}
+
+fn main() {}
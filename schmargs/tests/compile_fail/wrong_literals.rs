@@ -0,0 +1,20 @@
+use schmargs::Schmargs;
+
+/// Literals of the wrong kind
+#[derive(Schmargs)]
+struct Args {
+    /// Be verbose
+    #[arg(short = "v")]
+    verbose: bool,
+    /// Port
+    #[arg(long = 'p')]
+    port: u16,
+    /// Files
+    #[arg(max_len = "2")]
+    files: Vec<u8>,
+    /// Debug
+    #[arg(long, hide = 1)]
+    debug: bool,
+}
+
+fn main() {}
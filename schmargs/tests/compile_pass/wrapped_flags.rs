@@ -0,0 +1,44 @@
+use schmargs::{ArgsWithHelp, ArgsWithVersion, Schmargs};
+
+/// Options shared by all commands
+#[derive(Schmargs)]
+struct Common {
+    /// Be quiet
+    #[arg(short, long)]
+    quiet: bool,
+}
+
+/// Flags that don't conflict with each other or with the wrappers
+#[derive(Schmargs)]
+#[schmargs(show_hidden_with = "--help-all")]
+struct Args {
+    /// Shared options
+    #[arg(flatten)]
+    common: Common,
+    /// Print the hostname
+    #[arg(short = 'H', long)]
+    hostname: bool,
+}
+
+/// Options that use `-v`, so they can't be wrapped in `ArgsWithVersion`
+#[derive(Schmargs)]
+struct Verbose {
+    /// Be verbose
+    #[arg(short, long)]
+    verbose: bool,
+}
+
+/// Flattened flags that only conflict with a wrapper that isn't used
+#[derive(Schmargs)]
+struct HelpOnly {
+    /// Verbosity
+    #[arg(flatten)]
+    verbose: Verbose,
+}
+
+fn main() {
+    let args = ArgsWithVersion::<ArgsWithHelp<Args>>::parse("-q".split_whitespace());
+    assert!(args.is_ok());
+    let args = ArgsWithHelp::<HelpOnly>::parse("-v".split_whitespace());
+    assert!(args.is_ok());
+}
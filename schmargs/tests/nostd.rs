@@ -1,6 +1,6 @@
 //! Make sure this works in a #![no_std] environment
 #![no_std]
-use schmargs::Schmargs;
+use schmargs::{Schmargs, SchmargsValue};
 
 /// Bla bla bla
 #[derive(Schmargs)]
@@ -21,3 +21,111 @@ fn nostd_basic() {
     assert_eq!(args.positional2, 255);
     assert!(args.kill_all_humans);
 }
+
+#[derive(SchmargsValue, PartialEq, Debug)]
+enum Mode {
+    Fast,
+    #[value(alias = "careful")]
+    Slow,
+}
+
+/// Bla bla bla
+#[derive(Schmargs)]
+struct ArgsWithMode {
+    /// How fast to kill all humans
+    #[arg(short, long)]
+    mode: Mode,
+}
+
+#[test]
+fn nostd_value_enum() {
+    let args = ArgsWithMode::parse("-m careful".split_whitespace()).unwrap();
+    assert_eq!(args.mode, Mode::Slow);
+}
+
+#[test]
+fn nostd_arg_specs() {
+    use schmargs::spec::ArgKind;
+
+    let [positional, _, kill] = Args::ARGS else {
+        panic!("Expected 3 arguments");
+    };
+    assert_eq!(positional.kind, ArgKind::Positional);
+    assert_eq!(positional.value_name, "POSITIONAL");
+    assert_eq!(positional.description, "First positional argument");
+    assert!(!positional.optional && !positional.variadic);
+    assert_eq!(kill.kind, ArgKind::Flag);
+    assert_eq!((kill.short, kill.long), (Some('k'), Some("kill")));
+    assert!(kill.optional);
+    assert_eq!(kill.default_value, None);
+
+    let [mode] = ArgsWithMode::ARGS else {
+        panic!("Expected 1 argument");
+    };
+    assert_eq!(mode.possible_values, ["fast", "slow"]);
+}
+
+#[test]
+fn nostd_suggestions() {
+    use schmargs::SchmargsError;
+
+    assert_eq!(
+        Args::parse("--kil".split_whitespace()).err(),
+        Some(SchmargsError::NoSuchLongFlag {
+            got: "--kil",
+            suggestion: Some("kill")
+        })
+    );
+    assert_eq!(
+        ArgsWithMode::parse("--mode fsat".split_whitespace()).err(),
+        Some(SchmargsError::InvalidValue {
+            got: "fsat",
+            possible_values: &["fast", "slow"],
+            suggestion: Some("fast")
+        })
+    );
+}
+
+#[test]
+fn nostd_value_parser() {
+    use core::str::FromStr;
+    use schmargs::{SchmargsError, ValueParserError};
+
+    fn parse_name(_value: &str) -> Result<&'static str, &'static str> {
+        Err("names must be no longer than the capacity of a value parser error, which is 64 bytes")
+    }
+
+    /// Bla bla bla
+    #[derive(Schmargs)]
+    struct Args {
+        /// Number of humans
+        #[arg(value_parser = u8::from_str)]
+        humans: u8,
+        /// Name of the human
+        #[arg(value_parser = parse_name)]
+        name: Option<&'static str>,
+    }
+
+    let args = Args::parse("42".split_whitespace()).unwrap();
+    assert_eq!((args.humans, args.name), (42, None));
+    assert_eq!(
+        Args::parse("many".split_whitespace()).err(),
+        Some(SchmargsError::ValueParserFailed(
+            "humans",
+            ValueParserError::new(u8::from_str("many").unwrap_err())
+        ))
+    );
+
+    let Some(SchmargsError::ValueParserFailed("name", err)) =
+        Args::parse("42 bob".split_whitespace()).err()
+    else {
+        panic!("expected value parser error");
+    };
+    #[cfg(not(feature = "alloc"))]
+    assert_eq!(
+        err.as_str(),
+        "names must be no longer than the capacity of a value parser erro"
+    );
+    #[cfg(feature = "alloc")]
+    assert!(err.as_str().ends_with("which is 64 bytes"));
+}
@@ -1,3 +1,4 @@
+//! General tests for the derive macro
 #![allow(dead_code)]
 use schmargs::{Schmargs, SchmargsField};
 
@@ -161,7 +162,7 @@ fn help_arg() {
     }
 
     let args = ArgsWithHelp::<Args>::parse("--help".split_whitespace()).unwrap();
-    assert!(matches!(args, ArgsWithHelp::Help));
+    assert!(matches!(args, ArgsWithHelp::Help(_)));
 
     let args = ArgsWithHelp::<Args>::parse("8".split_whitespace()).unwrap();
     assert!(matches!(args, ArgsWithHelp::Args(Args { puppies: 8 })));
@@ -413,9 +414,2493 @@ fn default_values_positionals_and_options() {
     assert_eq!(args.speech, "Fuck you, puppy");
 }
 
+#[test]
+fn subcommands() {
+    #[derive(Schmargs)]
+    /// Puppy kicking crew
+    struct CrewArgs {
+        /// Number of crew members
+        members: u8,
+    }
+
+    #[derive(Schmargs)]
+    #[schmargs(name = "pupkick")]
+    /// Automatic puppy kicker
+    enum Args<'a> {
+        /// Kick a puppy
+        Kick {
+            /// Kick really hard
+            #[arg(short, long)]
+            hard: bool,
+            /// The puppy to kick
+            puppy: &'a str,
+        },
+        /// Hire a crew
+        HireCrew(CrewArgs),
+    }
+
+    let args = Args::parse("kick -h eddie".split_whitespace()).unwrap();
+    assert!(matches!(
+        args,
+        Args::Kick {
+            hard: true,
+            puppy: "eddie"
+        }
+    ));
+
+    let args = Args::parse("hire-crew 3".split_whitespace()).unwrap();
+    assert!(matches!(args, Args::HireCrew(CrewArgs { members: 3 })));
+
+    assert_eq!(
+        Args::parse("punch eddie".split_whitespace()).err(),
+        Some(schmargs::SchmargsError::NoSuchSubcommand {
+            got: "punch",
+            suggestion: None
+        })
+    );
+    assert_eq!(
+        Args::parse("".split_whitespace()).err(),
+        Some(schmargs::SchmargsError::ExpectedValue("COMMAND"))
+    );
+}
+
+#[test]
+fn nested_subcommands() {
+    #[derive(Schmargs)]
+    /// Manage puppies
+    enum PuppyArgs {
+        /// Adopt a puppy
+        Adopt {
+            /// How many to adopt
+            count: u8,
+        },
+        /// Release all puppies
+        #[schmargs(name = "free")]
+        Release {},
+    }
+
+    #[derive(Schmargs)]
+    /// Animal manager
+    enum Args {
+        /// Manage puppies
+        Puppy(PuppyArgs),
+    }
+
+    let args = Args::parse("puppy adopt 2".split_whitespace()).unwrap();
+    assert!(matches!(args, Args::Puppy(PuppyArgs::Adopt { count: 2 })));
+
+    let args = Args::parse("puppy free".split_whitespace()).unwrap();
+    assert!(matches!(args, Args::Puppy(PuppyArgs::Release {})));
+}
+
+#[test]
+fn subcommand_help_text() {
+    use schmargs::ArgsWithHelp;
+
+    #[derive(Schmargs)]
+    #[schmargs(name = "pupkick")]
+    /// Automatic puppy kicker
+    enum Args {
+        /// Kick a puppy
+        Kick {},
+        /// Pet a puppy
+        PetGently {},
+    }
+
+    assert_eq!(Args::USAGE, "pupkick <kick|pet-gently>");
+    assert_eq!(
+        format!("{}", Args::help()),
+        "Automatic puppy kicker
+
+Usage: pupkick <kick|pet-gently>
+
+Commands:
+kick       Kick a puppy
+pet-gently Pet a puppy"
+    );
+
+    let args = ArgsWithHelp::<Args>::parse("--help".split_whitespace()).unwrap();
+    assert!(matches!(args, ArgsWithHelp::Help(_)));
+}
+
+#[test]
+fn wrapped_subcommand_help() {
+    use schmargs::{ArgsWithHelp, ArgsWithVersion};
+
+    // Named after the crate, but shown after the commands it's nested in
+    /// Manage remotes
+    #[derive(Schmargs)]
+    enum RemoteArgs<'a> {
+        /// Add a remote
+        Add {
+            /// Fetch the remote right away
+            #[arg(short, long)]
+            fetch: bool,
+            /// Name of the remote
+            name: &'a str,
+        },
+        /// Remove a remote
+        Remove {
+            /// Name of the remote
+            name: &'a str,
+        },
+    }
+
+    /// Version control
+    #[derive(Schmargs)]
+    #[schmargs(name = "vc")]
+    enum Args<'a> {
+        /// Create a repository
+        Init {},
+        // Described by the doc comment of `RemoteArgs`
+        Remote(RemoteArgs<'a>),
+    }
+
+    // Options of wrappers get their own heading
+    let args = ArgsWithHelp::<Args>::parse("--help".split_whitespace()).unwrap();
+    assert_eq!(
+        format!("{args}"),
+        "Version control
+
+Usage: vc <init|remote>
+
+Commands:
+init       Create a repository
+remote     Manage remotes
+
+Options:
+-h, --help Print help"
+    );
+
+    // Help is shown for the subcommand it was asked for in
+    let args = ArgsWithHelp::<Args>::parse("init --help".split_whitespace()).unwrap();
+    assert_eq!(
+        format!("{args}"),
+        "Create a repository
+
+Usage: vc init
+
+Options:
+-h, --help Print help"
+    );
+    let args = ArgsWithHelp::<Args>::parse("remote -h".split_whitespace()).unwrap();
+    assert!(format!("{args}").starts_with("Manage remotes\n\nUsage: vc remote <add|remove>"));
+    let args = ArgsWithHelp::<Args>::parse("remote add origin --help".split_whitespace()).unwrap();
+    assert_eq!(
+        format!("{args}"),
+        "Add a remote
+
+Usage: vc remote add [OPTIONS] NAME
+
+Arguments:
+NAME        Name of the remote
+
+Options:
+-f, --fetch Fetch the remote right away
+-h, --help  Print help"
+    );
+
+    // Whichever way the wrappers are nested
+    let help = "Remove a remote
+
+Usage: vc remote remove NAME
+
+Arguments:
+NAME          Name of the remote
+
+Options:
+";
+    let args = ArgsWithVersion::<ArgsWithHelp<Args>>::parse("remote remove -h".split_whitespace())
+        .unwrap();
+    assert_eq!(
+        format!("{args}"),
+        format!("{help}-h, --help    Print help\n-v, --version Print version")
+    );
+    let args = ArgsWithHelp::<ArgsWithVersion<Args>>::parse("remote remove -h".split_whitespace())
+        .unwrap();
+    assert_eq!(
+        format!("{args}"),
+        format!("{help}-v, --version Print version\n-h, --help    Print help")
+    );
+
+    // Arguments are still passed on
+    let args = ArgsWithHelp::<Args>::parse("remote add -f origin".split_whitespace()).unwrap();
+    assert!(matches!(
+        args,
+        ArgsWithHelp::Args(Args::Remote(RemoteArgs::Add {
+            fetch: true,
+            name: "origin"
+        }))
+    ));
+}
+
+#[test]
+fn value_enum() {
+    use schmargs::{SchmargsError, SchmargsValue};
+
+    #[derive(SchmargsValue, Debug, PartialEq)]
+    enum Shoe {
+        Boot,
+        #[value(alias = "trainer", alias = "runner")]
+        Sneaker,
+        #[value(name = "flip-flop")]
+        Sandal,
+    }
+
+    #[derive(Schmargs)]
+    #[schmargs(name = "pupkick")]
+    /// Automatic puppy kicker
+    struct Args {
+        /// Shoe to kick with
+        #[arg(short, long)]
+        shoe: Shoe,
+        /// Shoe to use for the second kick
+        second_shoe: Option<Shoe>,
+    }
+
+    let args = Args::parse("-s sneaker".split_whitespace()).unwrap();
+    assert_eq!(args.shoe, Shoe::Sneaker);
+    assert_eq!(args.second_shoe, None);
+
+    let args = Args::parse("--shoe runner flip-flop".split_whitespace()).unwrap();
+    assert_eq!(args.shoe, Shoe::Sneaker);
+    assert_eq!(args.second_shoe, Some(Shoe::Sandal));
+
+    let err = Args::parse("--shoe sandal".split_whitespace())
+        .err()
+        .unwrap();
+    assert_eq!(
+        err,
+        SchmargsError::InvalidValue {
+            got: "sandal",
+            possible_values: &["boot", "sneaker", "flip-flop"],
+            suggestion: None
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "Invalid value 'sandal' (possible values: boot, sneaker, flip-flop)"
+    );
+
+    assert_eq!(
+        format!("{}", Args::help()),
+        "Automatic puppy kicker
+
+Usage: pupkick --shoe <SHOE> [SECOND_SHOE]
+
+Arguments:
+[SECOND_SHOE]     Shoe to use for the second kick [possible values: boot, sneaker, flip-flop]
+
+Options:
+-s, --shoe <SHOE> Shoe to kick with [possible values: boot, sneaker, flip-flop]"
+    );
+}
+
+#[test]
+fn flatten() {
+    #[derive(Schmargs)]
+    /// Shared puppy options
+    struct PuppyArgs<'a> {
+        /// Kick the puppy really hard
+        #[arg(short, long)]
+        hard: bool,
+        /// The puppy's name
+        #[arg(short, long)]
+        name: Option<&'a str>,
+        /// Where to kick the puppy
+        place: &'a str,
+    }
+
+    #[derive(Schmargs)]
+    #[schmargs(name = "pupkick")]
+    /// Automatic puppy kicker
+    struct Args<'a> {
+        /// Kick loudly
+        #[arg(short, long)]
+        loud: bool,
+        /// Puppy options
+        #[arg(flatten)]
+        puppy: PuppyArgs<'a>,
+        /// Number of times to kick
+        times: u8,
+    }
+
+    let args = Args::parse("-lh --name eddie park 3".split_whitespace()).unwrap();
+    assert!(args.loud);
+    assert!(args.puppy.hard);
+    assert_eq!(args.puppy.name, Some("eddie"));
+    assert_eq!(args.puppy.place, "park");
+    assert_eq!(args.times, 3);
+
+    assert_eq!(
+        Args::parse("-x park 3".split_whitespace()).err(),
+        Some(schmargs::SchmargsError::NoSuchShortFlag('x'))
+    );
+    assert_eq!(
+        Args::parse("-h".split_whitespace()).err(),
+        Some(schmargs::SchmargsError::ExpectedValue("PLACE"))
+    );
+
+    assert_eq!(Args::USAGE, "pupkick [OPTIONS] PLACE TIMES");
+    assert_eq!(
+        format!("{}", Args::help()),
+        "Automatic puppy kicker
+
+Usage: pupkick [OPTIONS] PLACE TIMES
+
+Arguments:
+PLACE             Where to kick the puppy
+TIMES             Number of times to kick
+
+Options:
+-l, --loud        Kick loudly
+-h, --hard        Kick the puppy really hard
+-n, --name <NAME> The puppy's name"
+    );
+}
+
+#[test]
+fn flatten_with_prefix() {
+    #[derive(Schmargs)]
+    /// Database options
+    struct DbArgs<'a> {
+        /// Host to connect to
+        #[arg(long)]
+        host: Option<&'a str>,
+        /// Port to connect to
+        #[arg(long, default_value = 5432)]
+        port: u16,
+    }
+
+    #[derive(Schmargs)]
+    /// Cache options
+    struct CacheArgs<'a> {
+        /// Database to use as a cache
+        #[arg(flatten, prefix = "db-")]
+        db: DbArgs<'a>,
+    }
+
+    #[derive(Schmargs)]
+    #[schmargs(name = "server")]
+    /// A server
+    struct Args<'a> {
+        /// Host to listen on
+        #[arg(long)]
+        host: &'a str,
+        /// Main database
+        #[arg(flatten, prefix = "db-")]
+        db: DbArgs<'a>,
+        /// Cache
+        #[arg(flatten, prefix = "cache-")]
+        cache: CacheArgs<'a>,
+    }
+
+    let args =
+        Args::parse("--host 0.0.0.0 --db-host db.local --cache-db-port 6379".split_whitespace())
+            .unwrap();
+    assert_eq!(args.host, "0.0.0.0");
+    assert_eq!(args.db.host, Some("db.local"));
+    assert_eq!(args.db.port, 5432);
+    assert_eq!(args.cache.db.host, None);
+    assert_eq!(args.cache.db.port, 6379);
+
+    assert_eq!(
+        Args::parse("--host 0.0.0.0 --port 1".split_whitespace()).err(),
+        Some(schmargs::SchmargsError::NoSuchLongFlag {
+            got: "--port",
+            suggestion: None
+        })
+    );
+
+    assert_eq!(Args::USAGE, "server --host <HOST> [OPTIONS]");
+    assert_eq!(
+        format!("{}", Args::help()),
+        "A server
+
+Usage: server --host <HOST> [OPTIONS]
+
+Options:
+--host <HOST>          Host to listen on
+--db-host <HOST>       Host to connect to
+--db-port <PORT>       Port to connect to
+--cache-db-host <HOST> Host to connect to
+--cache-db-port <PORT> Port to connect to"
+    );
+}
+
+#[test]
+fn long_option_with_equals() {
+    use schmargs::SchmargsError;
+
+    #[derive(Schmargs)]
+    /// Automatic puppy kicker
+    struct Args<'a> {
+        /// Kick the puppy really hard
+        #[arg(long)]
+        hard: bool,
+        /// What to say when kicking the puppy
+        #[arg(long)]
+        speech: Option<&'a str>,
+        /// Number of times to kick
+        #[arg(long)]
+        times: u8,
+    }
+
+    let args = Args::parse("--times=3 --speech=a=b".split_whitespace()).unwrap();
+    assert_eq!(args.times, 3);
+    assert_eq!(args.speech, Some("a=b"));
+
+    let args = Args::parse("--times 3 --speech=".split_whitespace()).unwrap();
+    assert_eq!(args.speech, Some(""));
+
+    assert_eq!(
+        Args::parse("--times=3 --hard=true".split_whitespace()).err(),
+        Some(SchmargsError::UnexpectedFlagValue("--hard"))
+    );
+    assert_eq!(
+        Args::parse("--times=3 --punch=true".split_whitespace()).err(),
+        Some(SchmargsError::NoSuchLongFlag {
+            got: "--punch",
+            suggestion: None
+        })
+    );
+}
+
+#[test]
+fn attached_short_option_values() {
+    #[derive(Schmargs)]
+    /// Tape archiver
+    struct Args<'a> {
+        /// Extract
+        #[arg(short = 'x')]
+        extract: bool,
+        /// Verbose
+        #[arg(short)]
+        verbose: bool,
+        /// Archive file
+        #[arg(short)]
+        file: Option<&'a str>,
+        /// Number of files
+        #[arg(short)]
+        number: Option<u32>,
+    }
+
+    let args = Args::parse("-xvfarchive.tar".split_whitespace()).unwrap();
+    assert!(args.extract);
+    assert!(args.verbose);
+    assert_eq!(args.file, Some("archive.tar"));
+
+    let args = Args::parse("-xvf archive.tar -n5".split_whitespace()).unwrap();
+    assert!(args.extract);
+    assert!(args.verbose);
+    assert_eq!(args.file, Some("archive.tar"));
+    assert_eq!(args.number, Some(5));
+
+    // The rest of the cluster is a value, not more flags
+    let args = Args::parse("-fxv".split_whitespace()).unwrap();
+    assert!(!args.extract);
+    assert!(!args.verbose);
+    assert_eq!(args.file, Some("xv"));
+
+    assert_eq!(
+        Args::parse("-xf".split_whitespace()).err(),
+        Some(schmargs::SchmargsError::ExpectedValue("-f"))
+    );
+}
+
+#[test]
+fn count_flags() {
+    #[derive(Schmargs)]
+    #[schmargs(name = "noisy")]
+    /// Noise maker
+    struct Args {
+        /// Increase verbosity
+        #[arg(short, long, action = count)]
+        verbose: u8,
+        /// Decrease verbosity
+        #[arg(short, action = count)]
+        quiet: usize,
+    }
+
+    let args = Args::parse("".split_whitespace()).unwrap();
+    assert_eq!(args.verbose, 0);
+    assert_eq!(args.quiet, 0);
+
+    let args = Args::parse("-vvv".split_whitespace()).unwrap();
+    assert_eq!(args.verbose, 3);
+
+    let args = Args::parse("-v -qv --verbose -q".split_whitespace()).unwrap();
+    assert_eq!(args.verbose, 3);
+    assert_eq!(args.quiet, 2);
+
+    assert_eq!(
+        Args::parse("--verbose=3".split_whitespace()).err(),
+        Some(schmargs::SchmargsError::UnexpectedFlagValue("--verbose"))
+    );
+
+    assert_eq!(Args::USAGE, "noisy [OPTIONS]");
+    assert_eq!(
+        format!("{}", Args::help()),
+        "Noise maker
+
+Usage: noisy [OPTIONS]
+
+Options:
+-v, --verbose... Increase verbosity
+-q...            Decrease verbosity"
+    );
+}
+
+#[test]
+fn negatable_flags() {
+    #[derive(Schmargs)]
+    #[schmargs(name = "ls")]
+    /// List files
+    struct Args {
+        /// Colorize the output
+        #[arg(short, long, negatable, default_value = true)]
+        color: bool,
+        /// Show hidden files
+        #[arg(long, negatable)]
+        all: bool,
+    }
+
+    let args = Args::parse("".split_whitespace()).unwrap();
+    assert!(args.color);
+    assert!(!args.all);
+
+    let args = Args::parse("--no-color --all".split_whitespace()).unwrap();
+    assert!(!args.color);
+    assert!(args.all);
+
+    // Last occurrence wins
+    let args = Args::parse("--no-color -c --all --no-all".split_whitespace()).unwrap();
+    assert!(args.color);
+    assert!(!args.all);
+
+    assert_eq!(
+        format!("{}", Args::help()),
+        "List files
+
+Usage: ls [OPTIONS]
+
+Options:
+-c, --[no-]color Colorize the output
+--[no-]all       Show hidden files"
+    );
+}
+
+#[test]
+fn negatable_flatten_with_prefix() {
+    #[derive(Schmargs)]
+    /// Cache options
+    struct CacheArgs {
+        /// Use the cache
+        #[arg(long, negatable, default_value = true)]
+        enable: bool,
+    }
+
+    #[derive(Schmargs)]
+    #[schmargs(name = "build")]
+    /// Build the project
+    struct Args {
+        /// Cache options
+        #[arg(flatten, prefix = "cache-")]
+        cache: CacheArgs,
+    }
+
+    let args = Args::parse("--cache-no-enable".split_whitespace()).unwrap();
+    assert!(!args.cache.enable);
+
+    assert_eq!(
+        format!("{}", Args::help()),
+        "Build the project
+
+Usage: build [OPTIONS]
+
+Options:
+--cache-[no-]enable Use the cache"
+    );
+}
+
+#[test]
+fn aliases() {
+    #[derive(Schmargs)]
+    #[schmargs(name = "kick")]
+    /// Automatic puppy kicker
+    struct Args<'a> {
+        /// Kick really hard
+        #[arg(
+            short,
+            long,
+            alias = "strong",
+            visible_alias = "hard",
+            visible_short_alias = 'H'
+        )]
+        forceful: bool,
+        /// Puppy to kick
+        #[arg(long, short_alias = 'p', alias = "dog", alias = "doggy")]
+        puppy: Option<&'a str>,
+    }
+
+    let args = Args::parse("-H --dog eddie".split_whitespace()).unwrap();
+    assert!(args.forceful);
+    assert_eq!(args.puppy, Some("eddie"));
+
+    let args = Args::parse("--strong --doggy=eddie".split_whitespace()).unwrap();
+    assert!(args.forceful);
+    assert_eq!(args.puppy, Some("eddie"));
+
+    let args = Args::parse("--hard -peddie".split_whitespace()).unwrap();
+    assert!(args.forceful);
+    assert_eq!(args.puppy, Some("eddie"));
+
+    assert_eq!(
+        format!("{}", Args::help()),
+        "Automatic puppy kicker
+
+Usage: kick [OPTIONS]
+
+Options:
+-f, --forceful  Kick really hard [aliases: -H, --hard]
+--puppy <PUPPY> Puppy to kick"
+    );
+}
+
+#[test]
+fn aliases_flatten_with_prefix() {
+    #[derive(Schmargs)]
+    /// Database options
+    struct DbArgs<'a> {
+        /// Database host
+        #[arg(long, visible_alias = "hostname")]
+        host: Option<&'a str>,
+    }
+
+    #[derive(Schmargs)]
+    #[schmargs(name = "serve")]
+    /// Serve the app
+    struct Args<'a> {
+        /// Database options
+        #[arg(flatten, prefix = "db-")]
+        db: DbArgs<'a>,
+    }
+
+    let args = Args::parse("--db-hostname localhost".split_whitespace()).unwrap();
+    assert_eq!(args.db.host, Some("localhost"));
+
+    assert_eq!(
+        format!("{}", Args::help()),
+        "Serve the app
+
+Usage: serve [OPTIONS]
+
+Options:
+--db-host <HOST> Database host [aliases: --db-hostname]"
+    );
+}
+
+#[test]
+fn value_constraints() {
+    use schmargs::SchmargsError;
+
+    #[derive(Schmargs)]
+    #[schmargs(name = "serve")]
+    /// Web server
+    struct Args<'a> {
+        /// Port to listen on
+        #[arg(short, long, range = 1..=65535, default_value = 80)]
+        port: u32,
+        /// Log level
+        #[arg(long, possible_values = ["debug", "info", "error"])]
+        log: Option<&'a str>,
+        /// Percentage of requests to trace
+        #[arg(long, range = 0..=100)]
+        trace: Option<u8>,
+    }
+
+    let args = Args::parse("-p 8080 --log info --trace 100".split_whitespace()).unwrap();
+    assert_eq!(args.port, 8080);
+    assert_eq!(args.log, Some("info"));
+    assert_eq!(args.trace, Some(100));
+
+    let args = Args::parse("".split_whitespace()).unwrap();
+    assert_eq!(args.port, 80);
+    assert_eq!(args.log, None);
+    assert_eq!(args.trace, None);
+
+    let err = Args::parse("-p 0".split_whitespace()).err().unwrap();
+    assert_eq!(
+        err,
+        SchmargsError::ConstraintViolation("--port", "range: 1..=65535")
+    );
+    assert_eq!(
+        err.to_string(),
+        "Invalid value for '--port' (range: 1..=65535)"
+    );
+
+    assert_eq!(
+        Args::parse("--trace 101".split_whitespace()).err(),
+        Some(SchmargsError::ConstraintViolation(
+            "--trace",
+            "range: 0..=100"
+        ))
+    );
+    assert_eq!(
+        Args::parse("--log warn".split_whitespace()).err(),
+        Some(SchmargsError::ConstraintViolation(
+            "--log",
+            "possible values: debug, info, error"
+        ))
+    );
+
+    assert_eq!(
+        format!("{}", Args::help()),
+        "Web server
+
+Usage: serve [OPTIONS]
+
+Options:
+-p, --port <PORT> Port to listen on [range: 1..=65535]
+--log <LOG>       Log level [possible values: debug, info, error]
+--trace <TRACE>   Percentage of requests to trace [range: 0..=100]"
+    );
+}
+
+#[test]
+fn value_parser() {
+    use core::str::FromStr;
+    use schmargs::{SchmargsError, ValueParserError};
+
+    #[derive(Debug, PartialEq)]
+    struct Size {
+        width: u32,
+        height: u32,
+    }
+
+    fn parse_size(value: &str) -> Result<Size, &'static str> {
+        let (width, height) = value.split_once('x').ok_or("expected WIDTHxHEIGHT")?;
+        Ok(Size {
+            width: width.parse().map_err(|_| "invalid width")?,
+            height: height.parse().map_err(|_| "invalid height")?,
+        })
+    }
+
+    #[derive(Schmargs)]
+    /// Resize images
+    struct Args<'a> {
+        /// Size of the thumbnail
+        #[arg(short, long, value_parser = parse_size)]
+        thumbnail: Option<Size>,
+        /// Quality of the resized image
+        #[arg(short, long, value_parser = u8::from_str)]
+        quality: Option<u8>,
+        /// Size to resize to
+        #[arg(value_parser = parse_size)]
+        size: Size,
+        /// Image to resize
+        image: &'a str,
+    }
+
+    let args = Args::parse("-t 32x32 640x480 cat.png".split_whitespace()).unwrap();
+    assert_eq!(
+        args.thumbnail,
+        Some(Size {
+            width: 32,
+            height: 32
+        })
+    );
+    assert_eq!(
+        args.size,
+        Size {
+            width: 640,
+            height: 480
+        }
+    );
+    assert_eq!(args.image, "cat.png");
+
+    let err = Args::parse("640 cat.png".split_whitespace()).err().unwrap();
+    assert_eq!(
+        err,
+        SchmargsError::ValueParserFailed("size", ValueParserError::new("expected WIDTHxHEIGHT"))
+    );
+    assert_eq!(
+        err.to_string(),
+        "Invalid value for 'size': expected WIDTHxHEIGHT"
+    );
+
+    let args = Args::parse("-q 90 640x480 cat.png".split_whitespace()).unwrap();
+    assert_eq!(args.quality, Some(90));
+    let err = Args::parse("-q 900 640x480 cat.png".split_whitespace())
+        .err()
+        .unwrap();
+    assert_eq!(
+        err,
+        SchmargsError::ValueParserFailed(
+            "quality",
+            ValueParserError::new(u8::from_str("900").unwrap_err())
+        )
+    );
+    assert_eq!(
+        err.to_string(),
+        "Invalid value for 'quality': number too large to fit in target type"
+    );
+
+    assert_eq!(
+        Args::parse("--thumbnail=32xbig 640x480 cat.png".split_whitespace()).err(),
+        Some(SchmargsError::ValueParserFailed(
+            "thumbnail",
+            ValueParserError::new("invalid height")
+        ))
+    );
+}
+
+#[test]
+fn argument_relations() {
+    use schmargs::SchmargsError;
+
+    #[derive(Schmargs)]
+    /// Convert files
+    struct Args<'a> {
+        /// Output file
+        #[arg(short, long, requires = format)]
+        output: Option<&'a str>,
+        /// Output format
+        #[arg(short, long)]
+        format: Option<&'a str>,
+        /// Read from stdin
+        #[arg(long, conflicts_with = file)]
+        stdin: bool,
+        /// Be verbose
+        #[arg(short, action = count, conflicts_with = [stdin])]
+        verbose: u8,
+        /// File to convert
+        #[arg(required_unless_present = [stdin])]
+        file: Option<&'a str>,
+    }
+
+    let args = Args::parse("-o out.json -f json in.yaml".split_whitespace()).unwrap();
+    assert_eq!(args.output, Some("out.json"));
+    assert_eq!(args.format, Some("json"));
+    assert_eq!(args.file, Some("in.yaml"));
+
+    let args = Args::parse("--stdin".split_whitespace()).unwrap();
+    assert!(args.stdin);
+    assert_eq!(args.file, None);
+
+    let err = Args::parse("-o out.json in.yaml".split_whitespace())
+        .err()
+        .unwrap();
+    assert_eq!(err, SchmargsError::Requires("--output", "--format"));
+    assert_eq!(err.to_string(), "'--output' requires '--format'");
+
+    let err = Args::parse("--stdin in.yaml".split_whitespace())
+        .err()
+        .unwrap();
+    assert_eq!(err, SchmargsError::ConflictsWith("--stdin", "FILE"));
+    assert_eq!(err.to_string(), "'--stdin' conflicts with 'FILE'");
+
+    assert_eq!(
+        Args::parse("-v --stdin".split_whitespace()).err(),
+        Some(SchmargsError::ConflictsWith("-v", "--stdin"))
+    );
+
+    let err = Args::parse("-f json".split_whitespace()).err().unwrap();
+    assert_eq!(
+        err,
+        SchmargsError::RequiredUnlessPresent("FILE", &["--stdin"])
+    );
+    assert_eq!(err.to_string(), "Expected 'FILE' or one of: --stdin");
+}
+
+#[test]
+fn argument_relations_with_defaults() {
+    use schmargs::SchmargsError;
+
+    #[derive(Schmargs)]
+    /// Shared options
+    struct Shared {
+        /// Number of jobs
+        #[arg(short, long, default_value = 1, conflicts_with = serial)]
+        jobs: u32,
+        /// Run serially
+        #[arg(long)]
+        serial: bool,
+    }
+
+    #[derive(Schmargs)]
+    /// Build things
+    struct Args {
+        /// Shared options
+        #[arg(flatten)]
+        shared: Shared,
+    }
+
+    // Default values don't count as being passed
+    let args = Args::parse("--serial".split_whitespace()).unwrap();
+    assert_eq!(args.shared.jobs, 1);
+    assert!(args.shared.serial);
+
+    assert_eq!(
+        Args::parse("-j 4 --serial".split_whitespace()).err(),
+        Some(SchmargsError::ConflictsWith("--jobs", "--serial"))
+    );
+
+    #[derive(Schmargs)]
+    #[schmargs(name = "cat")]
+    /// Print a file
+    struct Cat<'a> {
+        /// Read from stdin
+        #[arg(long)]
+        stdin: bool,
+        /// File to print
+        #[arg(default_value = "-", required_unless_present = [stdin])]
+        file: &'a str,
+    }
+
+    // Arguments that aren't an `Option` fall back on their default
+    assert_eq!(Cat::USAGE, "cat [OPTIONS] [FILE]");
+    let args = Cat::parse("--stdin".split_whitespace()).unwrap();
+    assert_eq!(args.file, "-");
+    let args = Cat::parse("notes.txt".split_whitespace()).unwrap();
+    assert_eq!(args.file, "notes.txt");
+    assert_eq!(
+        Cat::parse("".split_whitespace()).err(),
+        Some(SchmargsError::RequiredUnlessPresent("FILE", &["--stdin"]))
+    );
+}
+
+#[test]
+fn argument_groups() {
+    use schmargs::SchmargsError;
+
+    #[derive(Schmargs)]
+    #[schmargs(
+        name = "connect",
+        group(name = "transport", args = [tcp, udp, unix], required, multiple = false)
+    )]
+    /// Connect to a server
+    struct Args<'a> {
+        /// Use TCP
+        #[arg(long)]
+        tcp: bool,
+        /// Use UDP
+        #[arg(long)]
+        udp: bool,
+        /// Use a Unix socket
+        #[arg(long, value_name = "PATH")]
+        unix: Option<&'a str>,
+        /// Be verbose
+        #[arg(short, long)]
+        verbose: bool,
+        /// Address to connect to
+        address: Option<&'a str>,
+    }
+
+    assert_eq!(
+        Args::USAGE,
+        "connect [OPTIONS] <--tcp|--udp|--unix> [ADDRESS]"
+    );
+
+    let args = Args::parse("--udp localhost".split_whitespace()).unwrap();
+    assert!(args.udp);
+    assert!(!args.tcp);
+    assert_eq!(args.address, Some("localhost"));
+
+    let args = Args::parse("--unix /tmp/sock".split_whitespace()).unwrap();
+    assert_eq!(args.unix, Some("/tmp/sock"));
+
+    let err = Args::parse("-v localhost".split_whitespace())
+        .err()
+        .unwrap();
+    assert_eq!(
+        err,
+        SchmargsError::MissingGroup("transport", &["--tcp", "--udp", "--unix"])
+    );
+    assert_eq!(
+        err.to_string(),
+        "Expected one of the 'transport' arguments: --tcp, --udp, --unix"
+    );
+
+    let err = Args::parse("--tcp --unix /tmp/sock".split_whitespace())
+        .err()
+        .unwrap();
+    assert_eq!(
+        err,
+        SchmargsError::GroupConflict("transport", "--tcp", "--unix")
+    );
+    assert_eq!(
+        err.to_string(),
+        "Only one of the 'transport' arguments can be passed, got '--tcp' and '--unix'"
+    );
+
+    assert_eq!(
+        format!("{}", Args::help()),
+        "Connect to a server
+
+Usage: connect [OPTIONS] <--tcp|--udp|--unix> [ADDRESS]
+
+Arguments:
+[ADDRESS]     Address to connect to
+
+Transport:
+--tcp         Use TCP
+--udp         Use UDP
+--unix <PATH> Use a Unix socket
+
+Options:
+-v, --verbose Be verbose"
+    );
+}
+
+#[test]
+fn optional_argument_groups() {
+    use schmargs::SchmargsError;
+
+    #[derive(Schmargs)]
+    #[schmargs(name = "ls", group(name = "sort", args = [size, time], multiple = false))]
+    /// List files
+    struct Args {
+        /// Sort by size
+        #[arg(short = 'S')]
+        size: bool,
+        /// Sort by time
+        #[arg(short)]
+        time: bool,
+    }
+
+    #[derive(Schmargs)]
+    #[schmargs(name = "ls")]
+    /// List files
+    struct Outer {
+        /// Sorting
+        #[arg(flatten)]
+        inner: Args,
+    }
+
+    assert_eq!(Args::USAGE, "ls [-S|-t]");
+    assert_eq!(Outer::USAGE, "ls [OPTIONS]");
+
+    let args = Args::parse(core::iter::empty()).unwrap();
+    assert!(!args.size && !args.time);
+
+    assert_eq!(
+        Outer::parse("-S -t".split_whitespace()).err(),
+        Some(SchmargsError::GroupConflict("sort", "-S", "-t"))
+    );
+
+    assert_eq!(
+        format!("{}", Outer::help()),
+        "List files
+
+Usage: ls [OPTIONS]
+
+Sort:
+-S Sort by size
+-t Sort by time"
+    );
+}
+
+#[test]
+fn required_options() {
+    use schmargs::SchmargsError;
+
+    #[derive(Schmargs)]
+    #[schmargs(name = "prog")]
+    /// Do stuff
+    struct Args<'a> {
+        /// Name of the thing
+        #[arg(long)]
+        name: &'a str,
+        /// Number of threads
+        #[arg(short = 'j')]
+        jobs: u8,
+        /// Be verbose
+        #[arg(short, long)]
+        verbose: bool,
+        /// Output file
+        #[arg(short, long)]
+        output: Option<&'a str>,
+        /// Input file
+        file: &'a str,
+    }
+
+    #[derive(Schmargs)]
+    /// Database options
+    struct DbArgs<'a> {
+        /// Host to connect to
+        #[arg(long)]
+        host: &'a str,
+    }
+
+    #[derive(Schmargs)]
+    #[schmargs(name = "server")]
+    /// A server
+    struct Server<'a> {
+        /// Main database
+        #[arg(flatten, prefix = "db-")]
+        db: DbArgs<'a>,
+    }
+
+    assert_eq!(Args::USAGE, "prog --name <NAME> -j <JOBS> [OPTIONS] FILE");
+    assert_eq!(Server::USAGE, "server --db-host <HOST>");
+
+    let args = Args::parse("--name foo -j 4 bar".split_whitespace()).unwrap();
+    assert_eq!(args.name, "foo");
+    assert_eq!(args.jobs, 4);
+    assert_eq!(args.file, "bar");
+
+    let err = Args::parse("-j 4 bar".split_whitespace()).err().unwrap();
+    assert_eq!(err, SchmargsError::MissingRequiredArgument("--name"));
+    assert_eq!(err.to_string(), "Missing required option '--name'");
+
+    assert_eq!(
+        Args::parse("--name foo bar".split_whitespace()).err(),
+        Some(SchmargsError::MissingRequiredArgument("-j"))
+    );
+    assert_eq!(
+        Args::parse("--name foo -j 4".split_whitespace()).err(),
+        Some(SchmargsError::ExpectedValue("FILE"))
+    );
+    let err = Args::parse("-j 4 bar --name".split_whitespace())
+        .err()
+        .unwrap();
+    assert_eq!(err, SchmargsError::ExpectedValue("--name"));
+    assert_eq!(err.to_string(), "Expected value for '--name'");
+}
+
+#[test]
+fn undocumented() {
+    #[derive(Schmargs)]
+    #[schmargs(name = "nothing")]
+    struct Args {
+        #[arg(short)]
+        quiet: bool,
+    }
+
+    assert_eq!(Args::DESCRIPTION, "");
+    assert_eq!(
+        format!("{}", Args::help()),
+        "Usage: nothing [OPTIONS]
+
+Options:
+-q"
+    );
+}
+
+#[test]
+fn unit_struct() {
+    use schmargs::SchmargsError;
+
+    /// Halt the machine
+    #[derive(Schmargs)]
+    #[schmargs(name = "halt")]
+    struct Halt;
+
+    assert_eq!(Halt::USAGE, "halt");
+    assert_eq!(
+        format!("{}", Halt::help()),
+        "Halt the machine
+
+Usage: halt"
+    );
+    assert!(Halt::parse("".split_whitespace()).is_ok());
+    assert_eq!(
+        Halt::parse("now".split_whitespace()).err(),
+        Some(SchmargsError::UnexpectedValue("now"))
+    );
+    assert_eq!(
+        Halt::parse("--force".split_whitespace()).err(),
+        Some(SchmargsError::NoSuchLongFlag {
+            got: "--force",
+            suggestion: None
+        })
+    );
+}
+
+#[test]
+fn hidden_arguments() {
+    use schmargs::{ArgsWithHelp, ArgsWithVersion};
+
+    /// Serve files over HTTP
+    #[derive(Schmargs)]
+    #[schmargs(name = "serve", show_hidden_with = "--help-all")]
+    struct Args<'a> {
+        /// Port to listen on
+        #[arg(short, long, hide = false)]
+        port: Option<u16>,
+        /// Dump each request
+        #[arg(long, hide = true)]
+        debug_requests: bool,
+        /// Directory to serve
+        directory: &'a str,
+        /// Socket to inherit
+        #[arg(hide)]
+        socket: Option<u32>,
+    }
+
+    assert_eq!(Args::USAGE, "serve [OPTIONS] DIRECTORY");
+    assert_eq!(
+        format!("{}", Args::help()),
+        "Serve files over HTTP
+
+Usage: serve [OPTIONS] DIRECTORY
+
+Arguments:
+DIRECTORY         Directory to serve
+
+Options:
+-p, --port <PORT> Port to listen on"
+    );
+    assert_eq!(
+        format!("{}", Args::help_all()),
+        "Serve files over HTTP
+
+Usage: serve [OPTIONS] DIRECTORY
+
+Arguments:
+DIRECTORY         Directory to serve
+[SOCKET]          Socket to inherit
+
+Options:
+-p, --port <PORT> Port to listen on
+--debug-requests  Dump each request"
+    );
+
+    // Hidden arguments can still be parsed
+    let args = Args::parse("--debug-requests www 3".split_whitespace()).unwrap();
+    assert!(args.debug_requests);
+    assert_eq!(args.directory, "www");
+    assert_eq!(args.socket, Some(3));
+
+    let args = ArgsWithHelp::<Args>::parse("--help-all".split_whitespace()).unwrap();
+    assert!(matches!(args, ArgsWithHelp::Help(help) if help.shows_hidden()));
+    assert!(format!("{args}").contains("--debug-requests"));
+    let args = ArgsWithHelp::<Args>::parse("--help".split_whitespace()).unwrap();
+    assert!(matches!(args, ArgsWithHelp::Help(help) if !help.shows_hidden()));
+    assert!(!format!("{args}").contains("--debug-requests"));
+
+    // The flag works whichever way the wrappers are nested
+    let args =
+        ArgsWithVersion::<ArgsWithHelp<Args>>::parse("--help-all".split_whitespace()).unwrap();
+    assert!(matches!(
+        args,
+        ArgsWithVersion::Args(ArgsWithHelp::Help(help)) if help.shows_hidden()
+    ));
+    assert!(format!("{args}").contains("--debug-requests"));
+    assert!(format!("{args}").contains("--version"));
+    let args =
+        ArgsWithHelp::<ArgsWithVersion<Args>>::parse("--help-all".split_whitespace()).unwrap();
+    assert!(matches!(args, ArgsWithHelp::Help(help) if help.shows_hidden()));
+    assert!(format!("{args}").contains("--debug-requests"));
+    assert!(format!("{args}").contains("--version"));
+    let args = ArgsWithVersion::<ArgsWithHelp<Args>>::parse("--help".split_whitespace()).unwrap();
+    assert!(!format!("{args}").contains("--debug-requests"));
+}
+
+#[test]
+fn hidden_groups() {
+    /// Connect somewhere
+    #[derive(Schmargs)]
+    #[schmargs(
+        name = "connect",
+        group(name = "debugging", args = [trace, dump]),
+        group(name = "transport", args = [tcp, udp, quic], required),
+    )]
+    struct Args {
+        /// Use TCP
+        #[arg(long)]
+        tcp: bool,
+        /// Use UDP
+        #[arg(long)]
+        udp: bool,
+        /// Use QUIC
+        #[arg(long, hide)]
+        quic: bool,
+        /// Trace packets
+        #[arg(long, hide)]
+        trace: bool,
+        /// Dump packets
+        #[arg(long, hide)]
+        dump: bool,
+        /// Secret key
+        #[arg(long, hide)]
+        key: u64,
+    }
+
+    assert_eq!(Args::USAGE, "connect <--tcp|--udp>");
+    assert_eq!(
+        format!("{}", Args::help()),
+        "Connect somewhere
+
+Usage: connect <--tcp|--udp>
+
+Transport:
+--tcp Use TCP
+--udp Use UDP"
+    );
+    assert_eq!(
+        format!("{}", Args::help_all()),
+        "Connect somewhere
+
+Usage: connect <--tcp|--udp>
+
+Debugging:
+--trace     Trace packets
+--dump      Dump packets
+
+Transport:
+--tcp       Use TCP
+--udp       Use UDP
+--quic      Use QUIC
+
+Options:
+--key <KEY> Secret key"
+    );
+    assert!(
+        Args::parse("--quic --key 1".split_whitespace())
+            .unwrap()
+            .quic
+    );
+}
+
+#[test]
+fn word_wrapping() {
+    use schmargs::{ArgsWithHelp, SchmargsValue};
+
+    #[derive(SchmargsValue)]
+    enum Color {
+        Auto,
+        Always,
+        Never,
+    }
+
+    /// List the files of a directory, sorted by name unless told otherwise
+    #[derive(Schmargs)]
+    #[schmargs(name = "ls")]
+    struct Args<'a> {
+        /// Show files whose names start with a dot
+        ///
+        /// The current and parent directories are shown too.
+        #[arg(short, long)]
+        all: bool,
+        /// When to color the output
+        #[arg(long)]
+        color: Option<Color>,
+        /// Directory to list
+        directory: Option<&'a str>,
+    }
+
+    let mut help = String::new();
+    ArgsWithHelp::<Args>::write_help_with_width(&mut help, 0, 40).unwrap();
+    assert_eq!(
+        help,
+        "List the files of a directory, sorted by
+name unless told otherwise
+
+Usage: ls [OPTIONS] [DIRECTORY]
+
+Arguments:
+[DIRECTORY]     Directory to list
+
+Options:
+-a, --all       Show files whose names
+                start with a dot
+--color <COLOR> When to color the output
+                [possible values: auto,
+                always, never]
+-h, --help      Print help"
+    );
+
+    assert_eq!(
+        format!("{}", Args::long_help().with_width(40)),
+        "List the files of a directory, sorted by
+name unless told otherwise
+
+Usage: ls [OPTIONS] [DIRECTORY]
+
+Arguments:
+[DIRECTORY]     Directory to list
+
+Options:
+-a, --all       Show files whose names
+                start with a dot
+
+                The current and parent
+                directories are shown
+                too.
+--color <COLOR> When to color the output
+                [possible values: auto,
+                always, never]"
+    );
+
+    // Words longer than the width aren't broken
+    assert_eq!(
+        format!("{}", Args::help().with_width(10)),
+        "List the
+files of a
+directory,
+sorted by
+name
+unless
+told
+otherwise
+
+Usage: ls [OPTIONS] [DIRECTORY]
+
+Arguments:
+[DIRECTORY]     Directory
+                to
+                list
+
+Options:
+-a, --all       Show
+                files
+                whose
+                names
+                start
+                with
+                a
+                dot
+--color <COLOR> When
+                to
+                color
+                the
+                output
+                [possible
+                values:
+                auto,
+                always,
+                never]"
+    );
+}
+
+#[test]
+fn word_wrapping_subcommands() {
+    /// Manage packages
+    #[derive(Schmargs)]
+    #[schmargs(name = "pkg")]
+    enum Args {
+        /// Install packages, along with everything they depend on
+        Install {},
+        /// Remove packages
+        Remove {},
+    }
+
+    assert_eq!(
+        format!("{}", Args::help().with_width(30)),
+        "Manage packages
+
+Usage: pkg <install|remove>
+
+Commands:
+install Install packages,
+        along with everything
+        they depend on
+remove  Remove packages"
+    );
+}
+
+#[test]
+fn suggestions() {
+    use schmargs::{ArgsWithHelp, SchmargsError, SchmargsValue, StrippedSchmargsError};
+
+    #[derive(SchmargsValue, Debug)]
+    enum Format {
+        Json,
+        Yaml,
+    }
+
+    /// Output options
+    #[derive(Schmargs, Debug)]
+    struct Output {
+        /// Output format
+        #[arg(long)]
+        format: Option<Format>,
+    }
+
+    /// Network options
+    #[derive(Schmargs, Debug)]
+    struct Network {
+        /// Request timeout
+        #[arg(long)]
+        timeout: Option<u32>,
+    }
+
+    /// Show the working tree status
+    #[derive(Schmargs, Debug)]
+    enum Args {
+        /// Show the working tree status
+        Status {
+            /// Be verbose
+            #[arg(short, long, visible_alias = "chatty")]
+            verbose: bool,
+            /// Print debug info
+            #[arg(long, hide)]
+            debug: bool,
+            /// Colorize the output
+            #[arg(long, negatable)]
+            color: bool,
+            #[arg(flatten)]
+            output: Output,
+            #[arg(flatten, prefix = "net-")]
+            network: Network,
+        },
+        /// Show commit logs
+        Log {},
+    }
+
+    let err = Args::parse("status --verbos".split_whitespace()).unwrap_err();
+    assert_eq!(
+        err,
+        SchmargsError::NoSuchLongFlag {
+            got: "--verbos",
+            suggestion: Some("verbose")
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "Found invalid option: '--verbos', did you mean '--verbose'?"
+    );
+    assert_eq!(
+        err.strip().to_string(),
+        "Found invalid option, did you mean '--verbose'?"
+    );
+
+    let suggestion = |args: &str| match Args::parse(args.split_whitespace()).unwrap_err() {
+        SchmargsError::NoSuchLongFlag { suggestion, .. } => suggestion,
+        err => panic!("Unexpected error: {err:?}"),
+    };
+    assert_eq!(suggestion("status --chaty"), Some("chatty"));
+    assert_eq!(suggestion("status --frmat=json"), Some("format"));
+    assert_eq!(suggestion("status --colour"), Some("color"));
+    assert_eq!(suggestion("status --no-colour"), Some("no-color"));
+    // Flags of flattened fields are suggested with their prefix
+    assert_eq!(suggestion("status --net-timout 1"), Some("net-timeout"));
+    assert_eq!(suggestion("status --nte-timeout 1"), Some("net-timeout"));
+    // Hidden arguments aren't suggested
+    assert_eq!(suggestion("status --debg"), None);
+    assert_eq!(suggestion("status --quiet"), None);
+
+    let err = Args::parse("stats".split_whitespace()).unwrap_err();
+    assert_eq!(
+        err,
+        SchmargsError::NoSuchSubcommand {
+            got: "stats",
+            suggestion: Some("status")
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "Found invalid subcommand: 'stats', did you mean 'status'?"
+    );
+    assert_eq!(
+        Args::parse("push".split_whitespace()).unwrap_err(),
+        SchmargsError::NoSuchSubcommand {
+            got: "push",
+            suggestion: None
+        }
+    );
+
+    let err = Args::parse("status --format jsn".split_whitespace()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Invalid value 'jsn' (possible values: json, yaml), did you mean 'json'?"
+    );
+    assert_eq!(
+        err.strip(),
+        StrippedSchmargsError::InvalidValue {
+            possible_values: &["json", "yaml"],
+            suggestion: Some("json")
+        }
+    );
+
+    // Wrappers suggest their own flag too
+    assert_eq!(
+        ArgsWithHelp::<Args>::parse("--hepl".split_whitespace()).unwrap_err(),
+        SchmargsError::NoSuchLongFlag {
+            got: "--hepl",
+            suggestion: Some("help")
+        }
+    );
+    assert_eq!(
+        ArgsWithHelp::<Args>::parse("status --verbse".split_whitespace()).unwrap_err(),
+        SchmargsError::NoSuchLongFlag {
+            got: "--verbse",
+            suggestion: Some("verbose")
+        }
+    );
+}
+
 #[cfg(feature = "std")]
 mod with_feature_std {
     use super::*;
+    use schmargs::{SchmargsError, ValueParserError};
+
+    #[test]
+    fn env_fallback() {
+        #[derive(Schmargs)]
+        #[schmargs(name = "server", env_prefix = "SCHMARGS_TEST_ENV_")]
+        /// Web server
+        struct Args<'a> {
+            /// Port to listen on
+            #[arg(short, long, env = "SCHMARGS_TEST_PORT", default_value = 80)]
+            port: u16,
+            /// Host to listen on
+            #[arg(long, env)]
+            host: Option<&'a str>,
+            /// Directory to serve
+            #[arg(env)]
+            root: &'a str,
+        }
+
+        std::env::remove_var("SCHMARGS_TEST_PORT");
+        std::env::remove_var("SCHMARGS_TEST_ENV_HOST");
+        std::env::remove_var("SCHMARGS_TEST_ENV_ROOT");
+
+        assert_eq!(
+            Args::parse("".split_whitespace()).err(),
+            Some(SchmargsError::ExpectedValue("ROOT"))
+        );
+
+        let args = Args::parse("/srv".split_whitespace()).unwrap();
+        assert_eq!(args.port, 80);
+        assert_eq!(args.host, None);
+        assert_eq!(args.root, "/srv");
+
+        std::env::set_var("SCHMARGS_TEST_PORT", "8080");
+        std::env::set_var("SCHMARGS_TEST_ENV_HOST", "localhost");
+        std::env::set_var("SCHMARGS_TEST_ENV_ROOT", "/var/www");
+
+        // The environment takes precedence over the default value
+        let args = Args::parse("".split_whitespace()).unwrap();
+        assert_eq!(args.port, 8080);
+        assert_eq!(args.host, Some("localhost"));
+        assert_eq!(args.root, "/var/www");
+
+        // The command line takes precedence over the environment
+        let args = Args::parse("--port 443 --host example.com /srv".split_whitespace()).unwrap();
+        assert_eq!(args.port, 443);
+        assert_eq!(args.host, Some("example.com"));
+        assert_eq!(args.root, "/srv");
+
+        std::env::set_var("SCHMARGS_TEST_PORT", "http");
+        assert!(matches!(
+            Args::parse("".split_whitespace()),
+            Err(SchmargsError::ParseInt(_))
+        ));
+
+        assert_eq!(
+            format!("{}", Args::help()),
+            "Web server
+
+Usage: server [OPTIONS] ROOT
+
+Arguments:
+ROOT              Directory to serve [env: SCHMARGS_TEST_ENV_ROOT]
+
+Options:
+-p, --port <PORT> Port to listen on [env: SCHMARGS_TEST_PORT]
+--host <HOST>     Host to listen on [env: SCHMARGS_TEST_ENV_HOST]"
+        );
+    }
+
+    #[test]
+    fn terminal_width() {
+        use schmargs::ArgsWithHelp;
+
+        /// Print a friendly greeting to the person with the given name, followed by a short poem about their day
+        #[derive(Schmargs)]
+        #[schmargs(name = "hello")]
+        struct Args<'a> {
+            /// Name of the person to greet
+            name: &'a str,
+        }
+
+        // Wider than any other help text in these tests, which may run concurrently
+        std::env::set_var("COLUMNS", "100");
+        let help = "Print a friendly greeting to the person with the given name, followed by a short poem about their
+day
+
+Usage: hello NAME
+
+Arguments:
+NAME Name of the person to greet";
+        assert_eq!(format!("{}", Args::help()), help);
+        assert_eq!(
+            format!("{}", Args::help().with_width(20).with_terminal_width()),
+            help
+        );
+
+        // Displaying the parsed arguments wraps them the same way
+        let args = ArgsWithHelp::<Args>::parse(["-h"].into_iter()).unwrap();
+        let (description, _) = help.split_once("\n\n").unwrap();
+        assert!(format!("{args}").starts_with(&format!("{description}\n\n")));
+    }
+
+    #[test]
+    fn completions() {
+        use schmargs::completions::{generate, Shell};
+        use schmargs::{ArgsWithHelp, SchmargsValue};
+        use std::path::PathBuf;
+
+        #[derive(SchmargsValue)]
+        enum Color {
+            Auto,
+            Never,
+        }
+
+        /// Connection options
+        #[derive(Schmargs)]
+        #[schmargs(iterates_over = String)]
+        struct Connection {
+            /// Host to connect to
+            #[arg(long, value_hint = hostname)]
+            host: Option<String>,
+        }
+
+        /// Manage remotes
+        #[derive(Schmargs)]
+        #[schmargs(iterates_over = String)]
+        enum Remote {
+            /// Add a remote
+            Add {
+                /// Name of the remote
+                name: String,
+            },
+        }
+
+        /// Synchronize files
+        #[derive(Schmargs)]
+        #[schmargs(name = "sync", iterates_over = String)]
+        enum Sync {
+            /// Push files to a remote
+            Push {
+                /// Be verbose
+                #[arg(short, long, action = count)]
+                verbose: u8,
+                /// Don't push, it's a test
+                #[arg(short = 'n', long, visible_alias = "pretend")]
+                dry_run: bool,
+                /// When to color the output
+                #[arg(long)]
+                color: Option<Color>,
+                /// Work in this directory
+                #[arg(short = 'C', long, value_hint = dir)]
+                directory: Option<String>,
+                /// Print debug info
+                #[arg(long, hide)]
+                debug: bool,
+                #[arg(flatten, prefix = "remote-")]
+                connection: Connection,
+                /// Files to push
+                files: Vec<PathBuf>,
+            },
+            Remote(Remote),
+        }
+        type Args = ArgsWithHelp<Sync>;
+
+        let script = |shell| {
+            let mut script = Vec::new();
+            generate::<Args>(shell, &mut script).unwrap();
+            String::from_utf8(script).unwrap()
+        };
+        assert_eq!(
+            script(Shell::Bash),
+            r#"_sync() {
+    local i cur prev cmd opts
+    COMPREPLY=()
+    cur="${COMP_WORDS[COMP_CWORD]}"
+    prev="${COMP_WORDS[COMP_CWORD-1]}"
+    cmd="sync"
+
+    for ((i = 1; i < COMP_CWORD; i++)); do
+        case "${cmd},${COMP_WORDS[i]}" in
+            "sync,push")
+                cmd="sync__push"
+                ;;
+            "sync,remote")
+                cmd="sync__remote"
+                ;;
+            "sync__remote,add")
+                cmd="sync__remote__add"
+                ;;
+        esac
+    done
+
+    case "${cmd}" in
+        "sync")
+            opts="-h --help push remote"
+            COMPREPLY=($(compgen -W "${opts}" -- "${cur}"))
+            return 0
+            ;;
+        "sync__push")
+            opts="-v --verbose -n --dry-run --pretend --color -C --directory --remote-host"
+            case "${prev}" in
+                --color)
+                    COMPREPLY=($(compgen -W "auto never" -- "${cur}"))
+                    return 0
+                    ;;
+                -C|--directory)
+                    COMPREPLY=($(compgen -d -- "${cur}"))
+                    return 0
+                    ;;
+                --remote-host)
+                    COMPREPLY=($(compgen -A hostname -- "${cur}"))
+                    return 0
+                    ;;
+            esac
+            COMPREPLY=($(compgen -W "${opts}" -- "${cur}"))
+            COMPREPLY+=($(compgen -f -- "${cur}"))
+            return 0
+            ;;
+        "sync__remote")
+            opts="add"
+            COMPREPLY=($(compgen -W "${opts}" -- "${cur}"))
+            return 0
+            ;;
+        "sync__remote__add")
+            opts=""
+            COMPREPLY=($(compgen -W "${opts}" -- "${cur}"))
+            return 0
+            ;;
+    esac
+}
+
+complete -F _sync -o bashdefault -o default sync
+"#
+        );
+        assert_eq!(
+            script(Shell::Zsh),
+            r#"#compdef sync
+
+_sync() {
+    local curcontext="$curcontext" state line
+    _arguments -s -S -C \
+        '(-h --help)-h[Print help]' \
+        '(-h --help)--help[Print help]' \
+        ': :->command' \
+        '*:: :->args' \
+        && return 0
+
+    case $state in
+        command)
+            local -a commands
+            commands=(
+                'push:Push files to a remote'
+                'remote:Manage remotes'
+            )
+            _describe -t commands 'command' commands
+            ;;
+        args)
+            case $line[1] in
+                'push') _sync__push ;;
+                'remote') _sync__remote ;;
+            esac
+            ;;
+    esac
+}
+
+_sync__push() {
+    local curcontext="$curcontext" state line
+    _arguments -s -S -C \
+        '*-v[Be verbose]' \
+        '*--verbose[Be verbose]' \
+        '(-n --dry-run --pretend)-n[Don'\''t push, it'\''s a test]' \
+        '(-n --dry-run --pretend)--dry-run[Don'\''t push, it'\''s a test]' \
+        '(-n --dry-run --pretend)--pretend[Don'\''t push, it'\''s a test]' \
+        '(--color)--color=[When to color the output]:COLOR:(auto never)' \
+        '(-C --directory)-C+[Work in this directory]:DIRECTORY:_files -/' \
+        '(-C --directory)--directory=[Work in this directory]:DIRECTORY:_files -/' \
+        '(--remote-host)--remote-host=[Host to connect to]:HOST:_hosts' \
+        '*:FILES:_files' \
+        && return 0
+}
+
+_sync__remote() {
+    local curcontext="$curcontext" state line
+    _arguments -s -S -C \
+        ': :->command' \
+        '*:: :->args' \
+        && return 0
+
+    case $state in
+        command)
+            local -a commands
+            commands=(
+                'add:Add a remote'
+            )
+            _describe -t commands 'command' commands
+            ;;
+        args)
+            case $line[1] in
+                'add') _sync__remote__add ;;
+            esac
+            ;;
+    esac
+}
+
+_sync__remote__add() {
+    local curcontext="$curcontext" state line
+    _arguments -s -S -C \
+        ':NAME:_default' \
+        && return 0
+}
+
+if [ "$funcstack[1]" = "_sync" ]; then
+    _sync "$@"
+else
+    compdef _sync sync
+fi
+"#
+        );
+        assert_eq!(
+            script(Shell::Fish),
+            r#"complete -c sync -n "__fish_use_subcommand" -s h -l help -d 'Print help'
+complete -c sync -n "__fish_use_subcommand" -f -a 'push' -d 'Push files to a remote'
+complete -c sync -n "__fish_use_subcommand" -f -a 'remote' -d 'Manage remotes'
+complete -c sync -n "__fish_seen_subcommand_from push" -s v -l verbose -d 'Be verbose'
+complete -c sync -n "__fish_seen_subcommand_from push" -s n -l dry-run -l pretend -d 'Don\'t push, it\'s a test'
+complete -c sync -n "__fish_seen_subcommand_from push" -l color -r -f -a 'auto never' -d 'When to color the output'
+complete -c sync -n "__fish_seen_subcommand_from push" -s C -l directory -r -f -a '(__fish_complete_directories)' -d 'Work in this directory'
+complete -c sync -n "__fish_seen_subcommand_from push" -l remote-host -r -f -a '(__fish_print_hostnames)' -d 'Host to connect to'
+complete -c sync -n "__fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from add" -f -a 'add' -d 'Add a remote'
+"#
+        );
+    }
+
+    #[test]
+    fn completions_shell_value() {
+        use schmargs::completions::Shell;
+
+        /// Print a completion script
+        #[derive(Schmargs)]
+        struct Args {
+            /// Shell to print the completion script for
+            #[arg(long)]
+            generate_completions: Option<Shell>,
+        }
+
+        let args = Args::parse("--generate-completions zsh".split_whitespace()).unwrap();
+        assert_eq!(args.generate_completions, Some(Shell::Zsh));
+        assert_eq!(
+            Args::parse("--generate-completions csh".split_whitespace()).err(),
+            Some(SchmargsError::InvalidValue {
+                got: "csh",
+                possible_values: &["bash", "zsh", "fish"],
+                suggestion: Some("zsh")
+            })
+        );
+    }
+
+    #[test]
+    fn man_page() {
+        use schmargs::{ArgsWithHelp, SchmargsValue};
+
+        #[derive(SchmargsValue)]
+        enum Format {
+            Json,
+            Text,
+        }
+
+        /// Connection options
+        #[derive(Schmargs)]
+        struct Connection<'a> {
+            /// Host to connect to
+            #[arg(long, default_value = "localhost")]
+            host: &'a str,
+        }
+
+        /// Fetch a resource from a server
+        ///
+        /// Resources are printed to standard output. Use `--output` to write them to a file
+        /// instead.
+        #[derive(Schmargs)]
+        #[schmargs(name = "fetch")]
+        struct BareArgs<'a> {
+            /// Be verbose
+            #[arg(short, long, action = count)]
+            verbose: u8,
+            /// Follow redirects
+            #[arg(long, negatable, default_value = true)]
+            follow: bool,
+            /// Number of times to retry
+            ///
+            /// Retries happen after a delay of one second.
+            #[arg(short, long, default_value = 3, visible_alias = "tries")]
+            retries: u32,
+            /// Format of the output
+            #[arg(long, env = "FETCH_FORMAT")]
+            format: Option<Format>,
+            /// Print debug info
+            #[arg(long, hide)]
+            debug: bool,
+            #[arg(flatten, prefix = "proxy-")]
+            proxy: Connection<'a>,
+            /// Path of the resource, e.g. /index.html
+            path: &'a str,
+            /// File to write to
+            output: Option<&'a str>,
+        }
+        type Args<'a> = ArgsWithHelp<BareArgs<'a>>;
+
+        let mut page = Vec::new();
+        schmargs::man::generate::<Args>(&mut page).unwrap();
+        assert_eq!(
+            String::from_utf8(page).unwrap(),
+            r#".TH FETCH 1 "" "fetch 0.0.0"
+.SH NAME
+fetch \- Fetch a resource from a server
+.SH SYNOPSIS
+\fBfetch\fR [OPTIONS] PATH [OUTPUT]
+.SH DESCRIPTION
+Fetch a resource from a server
+.PP
+Resources are printed to standard output. Use `\-\-output` to write them to a file instead.
+.SH ARGUMENTS
+.TP
+\fIPATH\fR
+Path of the resource, e.g. /index.html
+.TP
+[\fIOUTPUT\fR]
+File to write to
+.SH OPTIONS
+.TP
+\fB\-v\fR, \fB\-\-verbose\fR...
+Be verbose
+.TP
+\fB\-\-[no\-]follow\fR
+Follow redirects [default: true]
+.TP
+\fB\-r\fR, \fB\-\-retries\fR \fIRETRIES\fR
+Number of times to retry
+.IP
+Retries happen after a delay of one second. [aliases: \-\-tries] [default: 3]
+.TP
+\fB\-\-format\fR \fIFORMAT\fR
+Format of the output [env: FETCH_FORMAT] [possible values: json, text]
+.TP
+\fB\-\-proxy\-host\fR \fIHOST\fR
+Host to connect to [default: localhost]
+.TP
+\fB\-h\fR, \fB\-\-help\fR
+Print help
+.SH VERSION
+v0.0.0
+"#
+        );
+    }
+
+    #[test]
+    fn man_page_subcommands() {
+        /// Manage packages
+        #[derive(Schmargs)]
+        #[schmargs(name = "pkg")]
+        enum Args {
+            /// Install packages
+            Install {},
+            /// Remove packages
+            Remove {
+                /// Remove configuration files too
+                #[arg(short, long)]
+                purge: bool,
+            },
+        }
+
+        let mut page = Vec::new();
+        schmargs::man::generate::<Args>(&mut page).unwrap();
+        assert_eq!(
+            String::from_utf8(page).unwrap(),
+            r#".TH PKG 1 "" "pkg 0.0.0"
+.SH NAME
+pkg \- Manage packages
+.SH SYNOPSIS
+\fBpkg\fR <install|remove>
+.br
+\fBpkg install\fR
+.br
+\fBpkg remove\fR [OPTIONS]
+.SH DESCRIPTION
+Manage packages
+.SH COMMANDS
+.TP
+\fBinstall\fR
+Install packages
+.TP
+\fBremove\fR
+Remove packages
+.SH VERSION
+v0.0.0
+"#
+        );
+    }
+
+    #[test]
+    fn markdown_reference() {
+        use schmargs::{ArgsWithHelp, SchmargsValue};
+
+        #[derive(SchmargsValue)]
+        enum Color {
+            Auto,
+            Never,
+        }
+
+        /// Manage remotes
+        #[derive(Schmargs)]
+        enum Remote<'a> {
+            /// Add a remote
+            ///
+            /// The remote is fetched from right away.
+            Add {
+                /// Branch to track
+                #[arg(short = 't', long = "track", default_value = "main")]
+                branch: &'a str,
+                /// Name of the remote
+                name: &'a str,
+                /// URL of the remote, e.g. https://example.com/repo|git
+                url: Option<&'a str>,
+            },
+            /// Remove a remote
+            Remove {
+                /// Names of the remotes
+                names: Vec<&'a str>,
+            },
+        }
+
+        /// The stupid content tracker
+        #[derive(Schmargs)]
+        #[schmargs(name = "git")]
+        enum BareArgs<'a> {
+            /// Show the working tree status
+            Status {
+                /// When to use colors
+                #[arg(long, env = "GIT_COLOR")]
+                color: Option<Color>,
+                /// Be verbose
+                #[arg(short, long)]
+                verbose: bool,
+            },
+            Remote(Remote<'a>),
+        }
+        type Args<'a> = ArgsWithHelp<BareArgs<'a>>;
+
+        let mut page = Vec::new();
+        schmargs::markdown::generate::<Args>(&mut page).unwrap();
+        assert_eq!(
+            String::from_utf8(page).unwrap(),
+            r#"# `git`
+
+The stupid content tracker
+
+## Usage
+
+```text
+git <status|remote>
+```
+
+## Options
+
+| Option | Description | Default |
+| --- | --- | --- |
+| `-h, --help` | Print help | |
+
+## Commands
+
+| Command | Description |
+| --- | --- |
+| [`status`](#git-status) | Show the working tree status |
+| [`remote`](#git-remote) | Manage remotes |
+
+## `git status`
+
+Show the working tree status
+
+### Usage
+
+```text
+git status [OPTIONS]
+```
+
+### Options
+
+| Option | Description | Default |
+| --- | --- | --- |
+| `--color <COLOR>` | When to use colors<br><br>Environment variable: `GIT_COLOR`<br><br>Possible values: `auto`, `never` | |
+| `-v, --verbose` | Be verbose | |
+
+## `git remote`
+
+Manage remotes
+
+### Usage
+
+```text
+git remote <add|remove>
+```
+
+### Commands
+
+| Command | Description |
+| --- | --- |
+| [`add`](#git-remote-add) | Add a remote |
+| [`remove`](#git-remote-remove) | Remove a remote |
+
+### `git remote add`
+
+Add a remote
+
+The remote is fetched from right away.
+
+#### Usage
+
+```text
+git remote add [OPTIONS] NAME [URL]
+```
+
+#### Arguments
+
+| Argument | Description | Default |
+| --- | --- | --- |
+| `NAME` | Name of the remote | |
+| `[URL]` | URL of the remote, e.g. https://example.com/repo\|git | |
+
+#### Options
+
+| Option | Description | Default |
+| --- | --- | --- |
+| `-t, --track <BRANCH>` | Branch to track | `main` |
+
+### `git remote remove`
+
+Remove a remote
+
+#### Usage
+
+```text
+git remote remove NAMES...
+```
+
+#### Arguments
+
+| Argument | Description | Default |
+| --- | --- | --- |
+| `NAMES...` | Names of the remotes | |
+"#
+        );
+    }
+
+    #[test]
+    fn multi_line_docs() {
+        use schmargs::ArgsWithHelp;
+
+        /// Copy files
+        ///
+        /// Copies each source file to the destination. Existing files are
+        /// overwritten.
+        #[derive(Schmargs)]
+        #[schmargs(name = "cp")]
+        struct Args<'a> {
+            /// Copy directories recursively
+            ///
+            /// Symbolic links are
+            /// not followed.
+            #[arg(short, long)]
+            recursive: bool,
+            #[arg(short, long)]
+            force: bool,
+            /// Files to copy
+            files: Vec<&'a str>,
+        }
+
+        assert_eq!(Args::DESCRIPTION, "Copy files");
+        assert_eq!(
+            Args::LONG_DESCRIPTION,
+            "Copy files\n\nCopies each source file to the destination. Existing files are overwritten."
+        );
+
+        assert_eq!(
+            format!("{}", Args::help()),
+            "Copy files
+
+Usage: cp [OPTIONS] FILES...
+
+Arguments:
+FILES...        Files to copy
+
+Options:
+-r, --recursive Copy directories recursively
+-f, --force"
+        );
+
+        assert_eq!(
+            format!("{}", ArgsWithHelp::<Args>::long_help()),
+            "Copy files
+
+Copies each source file to the destination. Existing files are overwritten.
+
+Usage: cp [OPTIONS] FILES...
+
+Arguments:
+FILES...        Files to copy
+
+Options:
+-r, --recursive Copy directories recursively
+
+                Symbolic links are not followed.
+-f, --force
+-h, --help      Print help"
+        );
+    }
+
+    #[test]
+    fn tuple_struct() {
+        use schmargs::SchmargsError;
+
+        /// Reboot the machine
+        #[derive(Schmargs)]
+        #[schmargs(name = "reboot")]
+        struct Reboot(
+            /// Seconds to wait
+            u32,
+        );
+
+        assert_eq!(Reboot::USAGE, "reboot ARG0");
+        assert_eq!(Reboot::parse("5".split_whitespace()).unwrap().0, 5);
+        assert_eq!(
+            Reboot::parse("".split_whitespace()).err(),
+            Some(SchmargsError::ExpectedValue("ARG0"))
+        );
+
+        /// Write a value to memory
+        #[derive(Schmargs)]
+        #[schmargs(name = "poke")]
+        struct Poke<'a>(
+            /// Address to write to
+            #[arg(value_name = "ADDRESS")]
+            usize,
+            /// Values to write
+            #[arg(value_name = "VALUE", min_len = 1)]
+            Vec<&'a str>,
+        );
+
+        assert_eq!(Poke::USAGE, "poke ADDRESS VALUE...");
+        assert_eq!(
+            format!("{}", Poke::help()),
+            "Write a value to memory
+
+Usage: poke ADDRESS VALUE...
+
+Arguments:
+ADDRESS  Address to write to
+VALUE... Values to write [min values: 1]"
+        );
+        let args = Poke::parse("0x1000 1 2".split_whitespace()).unwrap();
+        assert_eq!(args.0, 0x1000);
+        assert_eq!(args.1, vec!["1", "2"]);
+        assert_eq!(
+            Poke::parse("".split_whitespace()).err(),
+            Some(SchmargsError::ExpectedValue("ADDRESS"))
+        );
+    }
+
+    #[test]
+    fn owned_env_fallback() {
+        #[derive(Schmargs)]
+        #[schmargs(iterates_over = String)]
+        /// Web server
+        struct Args {
+            /// Hosts to listen on
+            #[arg(long, env = "SCHMARGS_TEST_OWNED_HOSTS")]
+            hosts: Vec<String>,
+        }
+
+        std::env::set_var("SCHMARGS_TEST_OWNED_HOSTS", "localhost,example.com");
+        let args = Args::parse(std::iter::empty()).unwrap();
+        assert_eq!(args.hosts, ["localhost", "example.com"]);
+    }
+
+    #[test]
+    fn collection_constraints() {
+        #[derive(Schmargs)]
+        #[schmargs(name = "cat")]
+        /// Concatenate files
+        struct Args<'a> {
+            /// Separator bytes
+            #[arg(short, long, range = 1..=127, max_len = 2)]
+            separator: Vec<u8>,
+            /// Files to concatenate
+            #[arg(min_len = 1)]
+            files: Vec<&'a str>,
+        }
+
+        let args = Args::parse("-s 10,13 a b".split_whitespace()).unwrap();
+        assert_eq!(args.separator, [10, 13]);
+        assert_eq!(args.files, ["a", "b"]);
+
+        assert_eq!(
+            Args::parse("-s 10,13,0 a".split_whitespace()).err(),
+            Some(SchmargsError::ConstraintViolation(
+                "--separator",
+                "range: 1..=127"
+            ))
+        );
+        assert_eq!(
+            Args::parse("-s 10,13,10 a".split_whitespace()).err(),
+            Some(SchmargsError::ConstraintViolation(
+                "--separator",
+                "max values: 2"
+            ))
+        );
+        assert_eq!(
+            Args::parse("-s 10 --".split_whitespace()).err(),
+            Some(SchmargsError::ExpectedValue("FILES"))
+        );
+
+        assert_eq!(
+            format!("{}", Args::help()),
+            "Concatenate files
+
+Usage: cat --separator <SEPARATOR>... FILES...
+
+Arguments:
+FILES...                       Files to concatenate [min values: 1]
+
+Options:
+-s, --separator <SEPARATOR>... Separator bytes [range: 1..=127] [max values: 2]"
+        );
+    }
+
+    #[test]
+    fn value_parser_collections() {
+        mod hex {
+            pub fn parse(value: &str) -> Result<u8, &'static str> {
+                u8::from_str_radix(value, 16).map_err(|_| "expected hex byte")
+            }
+        }
+
+        #[derive(Schmargs)]
+        /// Write bytes
+        struct Args {
+            /// Bytes to write first
+            #[arg(short, long, value_parser = hex::parse)]
+            header: Vec<u8>,
+            /// Bytes to write
+            #[arg(value_parser = hex::parse)]
+            bytes: Vec<u8>,
+        }
+
+        let args = Args::parse("--header ca,fe ff 00 7f".split_whitespace()).unwrap();
+        assert_eq!(args.header, [0xca, 0xfe]);
+        assert_eq!(args.bytes, [0xff, 0x00, 0x7f]);
+
+        assert_eq!(
+            Args::parse("ff zz".split_whitespace()).err(),
+            Some(SchmargsError::ValueParserFailed(
+                "bytes",
+                ValueParserError::new("expected hex byte")
+            ))
+        );
+    }
 
     #[test]
     fn owned_string() {
@@ -455,6 +2940,61 @@ mod with_feature_std {
         assert_eq!(args.numbers, vec![3, 1, 4, 1, 5]);
     }
 
+    #[test]
+    fn owned_long_option_with_equals() {
+        #[derive(Schmargs)]
+        #[schmargs(iterates_over = String)]
+        /// Automatic puppy kicker
+        struct Args {
+            /// The puppy to kick
+            #[arg(short, long)]
+            puppy: String,
+            /// Other puppies to kick
+            others: Vec<String>,
+        }
+
+        let arguments = "--puppy=Gus --x=y"
+            .split_whitespace()
+            .map(ToString::to_string);
+
+        assert_eq!(
+            Args::parse(arguments).err(),
+            Some(SchmargsError::NoSuchLongFlag {
+                got: String::from("--x"),
+                suggestion: None
+            })
+        );
+
+        let arguments = "--puppy=Gus -- Billy --x=y"
+            .split_whitespace()
+            .map(ToString::to_string);
+
+        let args = Args::parse(arguments).unwrap();
+        assert_eq!(args.puppy, "Gus");
+        assert_eq!(args.others, vec!["Billy", "--x=y"]);
+    }
+
+    #[test]
+    fn owned_attached_short_option_values() {
+        #[derive(Schmargs)]
+        #[schmargs(iterates_over = String)]
+        /// Tape archiver
+        struct Args {
+            /// Verbose
+            #[arg(short)]
+            verbose: bool,
+            /// Output file
+            #[arg(short)]
+            output: String,
+        }
+
+        let arguments = "-vofile.txt".split_whitespace().map(ToString::to_string);
+
+        let args = Args::parse(arguments).unwrap();
+        assert!(args.verbose);
+        assert_eq!(args.output, "file.txt");
+    }
+
     #[test]
     fn positional_string_vec() {
         #[derive(Schmargs)]